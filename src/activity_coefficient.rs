@@ -0,0 +1,241 @@
+use super::{DataSet, EstimatorError, Loss, Prediction};
+use crate::dataset::{join, select};
+use feos_core::{DensityInitialization, EosUnit, EquationOfState, State};
+use ndarray::{arr1, Array1};
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Activity coefficients at infinite dilution of a solute in a solvent
+/// as function of temperature and pressure.
+#[derive(Clone)]
+pub struct InfiniteDilutionActivityCoefficient<U: EosUnit> {
+    /// activity coefficient at infinite dilution
+    pub target: QuantityArray1<U>,
+    /// temperature
+    temperature: QuantityArray1<U>,
+    /// pressure
+    pressure: QuantityArray1<U>,
+    /// index of the solute in the equation of state
+    solute: usize,
+    /// index of the solvent in the equation of state
+    solvent: usize,
+    /// number of data points
+    datapoints: usize,
+}
+
+impl<U: EosUnit> InfiniteDilutionActivityCoefficient<U> {
+    /// Create a new data set for activity coefficients at infinite dilution.
+    ///
+    /// The target is dimensionless. `solute` and `solvent` are the
+    /// indices of two different components in the equation of state.
+    /// The indices are checked against the number of components of the
+    /// equation of state when the data set is evaluated.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        solute: usize,
+        solvent: usize,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature.len() != datapoints || pressure.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        if solute == solvent {
+            return Err(EstimatorError::IdenticalComponents(solute));
+        }
+        Ok(Self {
+            target,
+            temperature,
+            pressure,
+            solute,
+            solvent,
+            datapoints,
+        })
+    }
+
+    /// Returns temperature of data points.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.temperature.clone()
+    }
+
+    /// Returns pressure of data points.
+    pub fn pressure(&self) -> QuantityArray1<U> {
+        self.pressure.clone()
+    }
+
+    /// Returns the indices of solute and solvent.
+    pub fn components(&self) -> (usize, usize) {
+        (self.solute, self.solvent)
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        if self.solute != other.solute || self.solvent != other.solvent {
+            return Err(EstimatorError::IncompatibleDataSets);
+        }
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            join(&self.pressure, &other.pressure)?,
+            self.solute,
+            self.solvent,
+        )
+    }
+}
+
+/// Logarithm of the activity coefficient at infinite dilution
+/// of the solute in the liquid solvent.
+fn ln_gamma_inf<U: EosUnit, E: EquationOfState>(
+    eos: &Rc<E>,
+    solute_eos: &Rc<E>,
+    solute: usize,
+    moles: &QuantityArray1<U>,
+    temperature: QuantityScalar<U>,
+    pressure: QuantityScalar<U>,
+) -> Result<f64, EstimatorError> {
+    let mixture = State::new_npt(
+        eos,
+        temperature,
+        pressure,
+        moles,
+        DensityInitialization::Liquid,
+    )?;
+    let pure = State::new_npt(
+        solute_eos,
+        temperature,
+        pressure,
+        &(arr1(&[1.0]) * U::reference_moles()),
+        DensityInitialization::Liquid,
+    )?;
+    Ok(mixture.ln_phi()[solute] - pure.ln_phi()[0])
+}
+
+impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for InfiniteDilutionActivityCoefficient<U> {
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        "infinite dilution activity coefficient"
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        vec!["temperature", "pressure"]
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let n = eos.components();
+        for &i in [self.solute, self.solvent].iter() {
+            if i >= n {
+                return Err(EstimatorError::InvalidComponent(i, n));
+            }
+        }
+        let solute_eos = Rc::new(eos.subset(&[self.solute]));
+        let mut moles = Array1::zeros(n);
+        moles[self.solvent] = 1.0;
+        let moles = moles * U::reference_moles();
+
+        Prediction::from_results(
+            (0..self.datapoints).map(|i| {
+                ln_gamma_inf(
+                    eos,
+                    &solute_eos,
+                    self.solute,
+                    &moles,
+                    self.temperature.get(i),
+                    self.pressure.get(i),
+                )
+                .map(|ln_gamma| ln_gamma.exp().into())
+            }),
+            1.0.into(),
+        )
+    }
+
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut cost = self.relative_difference(eos)?;
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            select(&self.pressure, indices)?,
+            self.solute,
+            self.solvent,
+        )?))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(2);
+        m.insert("temperature".to_owned(), self.temperature());
+        m.insert("pressure".to_owned(), self.pressure());
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane_mixture;
+    use quantity::si::*;
+
+    #[test]
+    fn ideal_mixture() {
+        let eos = propane_mixture();
+        let data = InfiniteDilutionActivityCoefficient::new(
+            arr1(&[1.0, 1.0]).into(),
+            arr1(&[250.0, 280.0]) * KELVIN,
+            arr1(&[1e6, 2e6]) * PASCAL,
+            0,
+            1,
+        )
+        .unwrap();
+        let prediction = data.predict_with_status(&eos).unwrap();
+        assert!(prediction.failed().is_empty());
+        for &gamma in prediction.values.to_reduced(1.0.into()).unwrap().iter() {
+            assert!((gamma - 1.0).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let t = arr1(&[250.0, 280.0]) * KELVIN;
+        let p = arr1(&[1e6]) * PASCAL;
+        let target: SIArray1 = arr1(&[1.0, 1.0]).into();
+        assert!(matches!(
+            InfiniteDilutionActivityCoefficient::new(target.clone(), t.clone(), p, 0, 1),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+        let p = arr1(&[1e6, 2e6]) * PASCAL;
+        assert!(matches!(
+            InfiniteDilutionActivityCoefficient::new(target.clone(), t.clone(), p.clone(), 1, 1),
+            Err(EstimatorError::IdenticalComponents(1))
+        ));
+        let data = InfiniteDilutionActivityCoefficient::new(target, t, p, 0, 2).unwrap();
+        assert!(matches!(
+            data.predict(&propane_mixture()),
+            Err(EstimatorError::InvalidComponent(2, 2))
+        ));
+    }
+}
@@ -0,0 +1,519 @@
+use super::{DataSet, EstimatorError, Loss, Prediction, PredictionOptions};
+use crate::dataset::{join, select};
+use feos_core::{Contributions, EosResult, EosUnit, EquationOfState, PhaseEquilibrium};
+use ndarray::{arr1, concatenate, Array1, Axis};
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const GRID_POINTS: usize = 10;
+const STEP: f64 = 0.05;
+const X_MIN: f64 = 1e-3;
+const MAX_ITER: usize = 50;
+const TOL: f64 = 1e-10;
+
+/// Experimental azeotropic points of a binary mixture.
+///
+/// For isothermal data, the target is the azeotropic pressure,
+/// for isobaric data it is the azeotropic temperature.
+#[derive(Clone)]
+pub struct Azeotrope<U: EosUnit> {
+    /// azeotropic pressure or temperature
+    pub target: QuantityArray1<U>,
+    /// specified temperature or pressure
+    temperature_or_pressure: QuantityArray1<U>,
+    /// azeotropic mole fraction of the first component
+    molefracs: Array1<f64>,
+    /// `true` if the temperature is specified
+    isothermal: bool,
+    /// number of data points
+    datapoints: usize,
+    /// solver options
+    options: PredictionOptions,
+}
+
+impl<U: EosUnit> Azeotrope<U> {
+    /// Create a new data set for azeotropic points of a binary mixture.
+    ///
+    /// If `temperature_or_pressure` is a temperature, the target has to be
+    /// the azeotropic pressure and vice versa, otherwise an error is returned.
+    /// `molefracs` are the azeotropic mole fractions of the first component.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature_or_pressure: QuantityArray1<U>,
+        molefracs: Array1<f64>,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature_or_pressure.len() != datapoints || molefracs.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let isothermal = if temperature_or_pressure.has_unit(&U::reference_temperature()) {
+            true
+        } else if temperature_or_pressure.has_unit(&U::reference_pressure()) {
+            false
+        } else {
+            return Err(EstimatorError::NoTemperatureOrPressure);
+        };
+        let target_unit = if isothermal {
+            U::reference_pressure()
+        } else {
+            U::reference_temperature()
+        };
+        if !target.has_unit(&target_unit) {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        Ok(Self {
+            target,
+            temperature_or_pressure,
+            molefracs,
+            isothermal,
+            datapoints,
+            options,
+        })
+    }
+
+    /// Returns the specified temperature or pressure of the data points.
+    pub fn temperature_or_pressure(&self) -> QuantityArray1<U> {
+        self.temperature_or_pressure.clone()
+    }
+
+    /// Returns the azeotropic mole fractions of the first component.
+    pub fn molefracs(&self) -> Array1<f64> {
+        self.molefracs.clone()
+    }
+
+    /// Description of the specified variable.
+    fn specification_str(&self) -> &str {
+        if self.isothermal {
+            "temperature"
+        } else {
+            "pressure"
+        }
+    }
+
+    /// Difference between vapor and liquid mole fraction of the first
+    /// component and the pressure or temperature at the bubble point.
+    fn bubble_point<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+        temperature_or_pressure: QuantityScalar<U>,
+        molefrac: f64,
+        tp_init: Option<QuantityScalar<U>>,
+    ) -> EosResult<(f64, QuantityScalar<U>)>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let vle = PhaseEquilibrium::bubble_point(
+            eos,
+            temperature_or_pressure,
+            &arr1(&[molefrac, 1.0 - molefrac]),
+            tp_init,
+            None,
+            self.options.bubble_dew(),
+        )?;
+        let tp = if self.isothermal {
+            vle.vapor().pressure(Contributions::Total)
+        } else {
+            vle.vapor().temperature
+        };
+        Ok((vle.vapor().molefracs[0] - molefrac, tp))
+    }
+
+    /// Locate the azeotrope of the model that is closest to the
+    /// experimental composition.
+    ///
+    /// Starting at the experimental composition, the distance to both sides
+    /// is doubled until y - x changes its sign. Only if no sign change is
+    /// found, the bubble line is scanned on a coarse grid. The bracket is
+    /// refined using the Illinois variant of the regula falsi.
+    /// If no azeotrope is found, the error message of the bubble point
+    /// at the experimental composition is returned, if it failed.
+    fn azeotrope<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+        temperature_or_pressure: QuantityScalar<U>,
+        molefrac: f64,
+        tp_init: QuantityScalar<U>,
+    ) -> Result<(f64, QuantityScalar<U>), String>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let x0 = molefrac.clamp(X_MIN, 1.0 - X_MIN);
+        let error = match self.bubble_point(eos, temperature_or_pressure, x0, Some(tp_init)) {
+            Ok((f0, tp0)) if f0.abs() < TOL => return Ok((x0, tp0)),
+            Ok((f0, tp0)) => {
+                let mut sides = [Some((x0, f0, tp0)), Some((x0, f0, tp0))];
+                let mut distance = STEP;
+                while sides.iter().any(Option::is_some) {
+                    for (side, sign) in sides.iter_mut().zip([-1.0, 1.0]) {
+                        let (x, f, tp) = match *side {
+                            Some(point) => point,
+                            None => continue,
+                        };
+                        let x_new = (x0 + sign * distance).clamp(X_MIN, 1.0 - X_MIN);
+                        *side = None;
+                        if x_new == x {
+                            continue;
+                        }
+                        if let Ok((f_new, tp_new)) =
+                            self.bubble_point(eos, temperature_or_pressure, x_new, Some(tp))
+                        {
+                            if f * f_new <= 0.0 {
+                                return self
+                                    .refine(
+                                        eos,
+                                        temperature_or_pressure,
+                                        (x, f),
+                                        (x_new, f_new),
+                                        tp_new,
+                                    )
+                                    .ok_or_else(|| {
+                                        "the azeotrope iteration did not converge".to_owned()
+                                    });
+                            }
+                            *side = Some((x_new, f_new, tp_new));
+                        }
+                    }
+                    distance *= 2.0;
+                }
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        };
+
+        let mut tp_init = Some(tp_init);
+        let mut grid = Vec::with_capacity(GRID_POINTS);
+        for i in 0..GRID_POINTS {
+            let x = (i as f64 + 0.5) / GRID_POINTS as f64;
+            if let Ok((f, tp)) = self.bubble_point(eos, temperature_or_pressure, x, tp_init) {
+                grid.push((x, f, tp));
+                tp_init = Some(tp);
+            }
+        }
+        let mut azeotrope: Option<(f64, QuantityScalar<U>)> = None;
+        for w in grid.windows(2) {
+            let ((a, fa, _), (b, fb, tp)) = (w[0], w[1]);
+            if fa * fb > 0.0 {
+                continue;
+            }
+            if let Some((x, tp)) = self.refine(eos, temperature_or_pressure, (a, fa), (b, fb), tp) {
+                let closer = match azeotrope {
+                    Some((x0, _)) => (x - molefrac).abs() < (x0 - molefrac).abs(),
+                    None => true,
+                };
+                if closer {
+                    azeotrope = Some((x, tp));
+                }
+            }
+        }
+        azeotrope.ok_or_else(|| {
+            error.unwrap_or_else(|| "the model does not predict an azeotrope".to_owned())
+        })
+    }
+
+    /// Refine the root of y - x within the bracket `(a, fa)`, `(b, fb)`
+    /// using the Illinois variant of the regula falsi.
+    fn refine<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+        temperature_or_pressure: QuantityScalar<U>,
+        (mut a, mut fa): (f64, f64),
+        (mut b, mut fb): (f64, f64),
+        tp: QuantityScalar<U>,
+    ) -> Option<(f64, QuantityScalar<U>)>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let mut side = 0;
+        for _ in 0..MAX_ITER {
+            let c = (a * fb - b * fa) / (fb - fa);
+            let (fc, tpc) = self
+                .bubble_point(eos, temperature_or_pressure, c, Some(tp))
+                .ok()?;
+            if fc.abs() < TOL || (b - a).abs() < TOL {
+                return Some((c, tpc));
+            }
+            if fc * fb > 0.0 {
+                b = c;
+                fb = fc;
+                if side == -1 {
+                    fa *= 0.5;
+                }
+                side = -1;
+            } else {
+                a = c;
+                fa = fc;
+                if side == 1 {
+                    fb *= 0.5;
+                }
+                side = 1;
+            }
+        }
+        None
+    }
+
+    /// Returns the azeotropic pressures or temperatures and the azeotropic
+    /// mole fractions of the model. If no azeotrope is found, `NAN` is returned.
+    pub fn predict_azeotropes<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<(QuantityArray1<U>, Array1<f64>), EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let prediction = self.predict_azeotropes_with_status(eos)?;
+        Ok((prediction.0.values, prediction.1))
+    }
+
+    /// Azeotropic pressures or temperatures together with the status of
+    /// every data point, and the azeotropic mole fractions.
+    fn predict_azeotropes_with_status<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<(Prediction<U>, Array1<f64>), EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let azeotropes: Vec<_> = (0..self.datapoints)
+            .map(|i| {
+                let tp = self.temperature_or_pressure.get(i);
+                self.azeotrope(eos, tp, self.molefracs[i], self.target.get(i))
+            })
+            .collect();
+        let molefracs = azeotropes
+            .iter()
+            .map(|a| a.as_ref().map_or(f64::NAN, |&(x, _)| x))
+            .collect();
+        let prediction = Prediction::from_results(
+            azeotropes.into_iter().map(|a| a.map(|(_, tp)| tp)),
+            self.target.get(0),
+        )?;
+        Ok((prediction, molefracs))
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(
+                &self.temperature_or_pressure,
+                &other.temperature_or_pressure,
+            )?,
+            concatenate(Axis(0), &[self.molefracs.view(), other.molefracs.view()])?,
+            self.options,
+        )
+    }
+}
+
+impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for Azeotrope<U> {
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        if self.isothermal {
+            "azeotropic pressure"
+        } else {
+            "azeotropic temperature"
+        }
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        vec![self.specification_str()]
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_azeotropes(eos)?.0)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_azeotropes_with_status(eos)?.0)
+    }
+
+    /// The cost contains the relative difference of the azeotropic
+    /// pressure or temperature for each data point, followed by the
+    /// difference of the azeotropic mole fractions, i.e., it has
+    /// twice as many entries as the data set has data points.
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let (prediction, molefracs) = self.predict_azeotropes(eos)?;
+        let mut cost = concatenate![
+            Axis(0),
+            ((prediction - &self.target) / &self.target).into_value()?,
+            molefracs - &self.molefracs
+        ];
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn n_residuals(&self) -> usize {
+        2 * self.datapoints
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature_or_pressure, indices)?,
+            self.molefracs.select(Axis(0), indices),
+            self.options,
+        )?))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(2);
+        m.insert(
+            self.specification_str().to_owned(),
+            self.temperature_or_pressure(),
+        );
+        m.insert("molefracs".to_owned(), self.molefracs().into());
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feos_core::cubic::{PengRobinson, PengRobinsonParameters, PengRobinsonRecord};
+    use feos_core::joback::JobackRecord;
+    use feos_core::parameter::{Identifier, Parameter, PureRecord};
+    use ndarray::Array2;
+    use quantity::si::*;
+
+    fn binary_mixture(tc: [f64; 2], pc: [f64; 2], w: [f64; 2], k_ij: f64) -> Rc<PengRobinson> {
+        let records: Vec<PureRecord<PengRobinsonRecord, JobackRecord>> = (0..2)
+            .map(|i| {
+                let id = Identifier::new(&i.to_string(), None, None, None, None, None);
+                PureRecord::new(id, 80.0, PengRobinsonRecord::new(tc[i], pc[i], w[i]), None)
+            })
+            .collect();
+        let k_ij = Array2::from_shape_vec((2, 2), vec![0.0, k_ij, k_ij, 0.0]).unwrap();
+        let parameters = PengRobinsonParameters::from_records(records, k_ij);
+        Rc::new(PengRobinson::new(Rc::new(parameters)))
+    }
+
+    #[test]
+    fn isothermal_azeotrope() {
+        let eos = binary_mixture([562.0, 553.6], [48.9e5, 40.7e5], [0.212, 0.211], 0.05);
+        let data = Azeotrope::new(
+            arr1(&[1.0e5, 1.5e5]) * PASCAL,
+            arr1(&[350.0, 360.0]) * KELVIN,
+            arr1(&[0.5, 0.5]),
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        let (pressure, molefracs) = data.predict_azeotropes(&eos).unwrap();
+        for i in 0..2 {
+            let x = arr1(&[molefracs[i], 1.0 - molefracs[i]]);
+            let vle = PhaseEquilibrium::bubble_point(
+                &eos,
+                data.temperature_or_pressure.get(i),
+                &x,
+                Some(pressure.get(i)),
+                None,
+                Default::default(),
+            )
+            .unwrap();
+            assert!((vle.vapor().molefracs[0] - molefracs[i]).abs() < 1e-8);
+            let p = vle.vapor().pressure(Contributions::Total);
+            assert!(((p - pressure.get(i)) / p).into_value().unwrap().abs() < 1e-8);
+        }
+
+        let cost = DataSet::<SIUnit, PengRobinson>::cost(&data, &eos, Loss::Linear).unwrap();
+        assert_eq!(cost.len(), 4);
+        assert_eq!(DataSet::<SIUnit, PengRobinson>::n_residuals(&data), 4);
+    }
+
+    #[test]
+    fn isobaric_azeotrope() {
+        let eos = binary_mixture([562.0, 553.6], [48.9e5, 40.7e5], [0.212, 0.211], 0.05);
+        let data = Azeotrope::new(
+            arr1(&[350.0]) * KELVIN,
+            arr1(&[1.0e5]) * PASCAL,
+            arr1(&[0.5]),
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            DataSet::<SIUnit, PengRobinson>::target_str(&data),
+            "azeotropic temperature"
+        );
+        let input = DataSet::<SIUnit, PengRobinson>::get_input(&data);
+        assert_eq!(input["pressure"], arr1(&[1.0e5]) * PASCAL);
+        assert_eq!(input["molefracs"], arr1(&[0.5]).into());
+        let (temperature, molefracs) = data.predict_azeotropes(&eos).unwrap();
+        assert!(temperature.get(0) > 300.0 * KELVIN && temperature.get(0) < 400.0 * KELVIN);
+        assert!(molefracs[0] > 0.0 && molefracs[0] < 1.0);
+    }
+
+    #[test]
+    fn no_azeotrope() {
+        let eos = binary_mixture([369.96, 425.1], [42.5e5, 38.0e5], [0.153, 0.2], 0.0);
+        let data = Azeotrope::new(
+            arr1(&[1.0e6]) * PASCAL,
+            arr1(&[300.0]) * KELVIN,
+            arr1(&[0.5]),
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        let prediction = data.predict_with_status(&eos).unwrap();
+        assert!(prediction
+            .values
+            .get(0)
+            .to_reduced(PASCAL)
+            .unwrap()
+            .is_nan());
+        assert_eq!(
+            prediction.failed(),
+            vec![(0, "the model does not predict an azeotrope")]
+        );
+    }
+
+    #[test]
+    fn no_temperature_or_pressure() {
+        assert!(matches!(
+            Azeotrope::new(
+                arr1(&[1.0e5]) * PASCAL,
+                arr1(&[1.0]) * METER,
+                arr1(&[0.5]),
+                PredictionOptions::default(),
+            ),
+            Err(EstimatorError::NoTemperatureOrPressure)
+        ));
+    }
+
+    #[test]
+    fn incompatible_target() {
+        let options = PredictionOptions::default();
+        assert!(matches!(
+            Azeotrope::new(
+                arr1(&[350.0]) * KELVIN,
+                arr1(&[360.0]) * KELVIN,
+                arr1(&[0.5]),
+                options,
+            ),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+        assert!(matches!(
+            Azeotrope::new(
+                arr1(&[1.0e5]) * PASCAL,
+                arr1(&[1.0e5]) * PASCAL,
+                arr1(&[0.5]),
+                options,
+            ),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+    }
+}
@@ -0,0 +1,401 @@
+//! Bayesian parameter estimation using Markov chain Monte Carlo sampling.
+//!
+//! The cost of an [Estimator](crate::Estimator) is interpreted as
+//! standardized residuals of a Gaussian likelihood. Samples of the posterior
+//! distribution are drawn with the affine invariant ensemble sampler of
+//! Goodman and Weare (as used in `emcee`).
+use crate::EstimatorError;
+use ndarray::{s, Array1, Array2, Array3, Axis};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+
+/// Maximum number of attempts to find initial walkers with finite posterior.
+const MAX_INIT_ATTEMPTS: usize = 100;
+/// Window factor of the automatic windowing of the autocorrelation time.
+const AUTOCORRELATION_WINDOW: f64 = 5.0;
+
+/// Prior distribution of a single parameter.
+#[derive(Clone, Copy, Debug)]
+pub enum Prior {
+    /// Improper constant prior.
+    Flat,
+    /// Uniform distribution between `lower` and `upper`.
+    Uniform { lower: f64, upper: f64 },
+    /// Normal distribution.
+    Normal { mean: f64, std: f64 },
+    /// Log-normal distribution, i.e., the logarithm of the
+    /// parameter is normally distributed.
+    LogNormal { mean: f64, std: f64 },
+}
+
+impl Prior {
+    /// Logarithm of the probability density at `x`.
+    pub fn ln_pdf(&self, x: f64) -> f64 {
+        match *self {
+            Self::Flat => 0.0,
+            Self::Uniform { lower, upper } => {
+                if x >= lower && x <= upper {
+                    -(upper - lower).ln()
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+            Self::Normal { mean, std } => {
+                -0.5 * ((x - mean) / std).powi(2) - (std * (2.0 * PI).sqrt()).ln()
+            }
+            Self::LogNormal { mean, std } => {
+                if x > 0.0 {
+                    -0.5 * ((x.ln() - mean) / std).powi(2) - (x * std * (2.0 * PI).sqrt()).ln()
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+        }
+    }
+}
+
+/// Returns the logarithm of the (unnormalized) posterior probability.
+///
+/// The likelihood is Gaussian in the `residuals`, e.g., obtained from
+/// [Estimator::residuals](crate::Estimator::residuals), with standard
+/// deviation `sigma`. `priors` contains the prior of every parameter.
+/// Failed evaluations result in zero probability.
+pub fn log_posterior<'a, F>(
+    residuals: F,
+    priors: &'a [Prior],
+    sigma: f64,
+) -> impl Fn(&Array1<f64>) -> f64 + 'a
+where
+    F: Fn(&Array1<f64>) -> Result<Array1<f64>, EstimatorError> + 'a,
+{
+    move |x| {
+        if x.len() != priors.len() {
+            return f64::NEG_INFINITY;
+        }
+        let ln_prior: f64 = priors.iter().zip(x.iter()).map(|(p, &x)| p.ln_pdf(x)).sum();
+        if !ln_prior.is_finite() {
+            return f64::NEG_INFINITY;
+        }
+        match residuals(x) {
+            Ok(r) => {
+                let ln_p = ln_prior - 0.5 * r.mapv(|r| (r / sigma).powi(2)).sum();
+                if ln_p.is_nan() {
+                    f64::NEG_INFINITY
+                } else {
+                    ln_p
+                }
+            }
+            Err(_) => f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Samples drawn by the [EnsembleSampler].
+#[derive(Clone, Debug)]
+pub struct McmcResult {
+    /// positions of the walkers with shape (steps, walkers, parameters)
+    pub samples: Array3<f64>,
+    /// logarithm of the posterior with shape (steps, walkers)
+    pub ln_probability: Array2<f64>,
+    /// fraction of accepted proposals of each walker
+    pub acceptance_rates: Array1<f64>,
+    /// integrated autocorrelation time of each parameter in steps
+    pub autocorrelation_times: Array1<f64>,
+}
+
+impl McmcResult {
+    /// Returns the samples of all walkers as (samples, parameters) array,
+    /// discarding the first `discard` steps and using only every `thin`-th step.
+    pub fn flat_samples(&self, discard: usize, thin: usize) -> Result<Array2<f64>, EstimatorError> {
+        let steps = self.samples.len_of(Axis(0));
+        if discard > steps {
+            return Err(EstimatorError::InvalidDiscard(discard, steps));
+        }
+        let chain = self.samples.slice(s![discard..;thin.max(1), .., ..]);
+        let (steps, walkers, n) = chain.dim();
+        Ok(chain.to_owned().into_shape((steps * walkers, n))?)
+    }
+}
+
+/// Affine invariant ensemble sampler using stretch moves.
+///
+/// The results are deterministic for a given seed.
+#[derive(Clone, Copy, Debug)]
+pub struct EnsembleSampler {
+    /// number of walkers
+    pub walkers: usize,
+    /// number of steps
+    pub steps: usize,
+    /// scale parameter of the stretch move
+    pub stretch: f64,
+    /// spread of the initial walkers relative to the initial parameters
+    pub initial_spread: f64,
+    /// seed of the random number generator
+    pub seed: u64,
+}
+
+impl Default for EnsembleSampler {
+    fn default() -> Self {
+        Self {
+            walkers: 32,
+            steps: 1000,
+            stretch: 2.0,
+            initial_spread: 1e-4,
+            seed: 0,
+        }
+    }
+}
+
+impl EnsembleSampler {
+    /// Set the number of walkers.
+    pub fn walkers(mut self, walkers: usize) -> Self {
+        self.walkers = walkers;
+        self
+    }
+
+    /// Set the number of steps.
+    pub fn steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Set the scale parameter of the stretch move.
+    pub fn stretch(mut self, stretch: f64) -> Self {
+        self.stretch = stretch;
+        self
+    }
+
+    /// Set the relative spread of the initial walkers.
+    pub fn initial_spread(mut self, initial_spread: f64) -> Self {
+        self.initial_spread = initial_spread;
+        self
+    }
+
+    /// Set the seed of the random number generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sample the distribution with logarithmic probability density `ln_prob`
+    /// using walkers that are initialized in a small ball around `x0`.
+    pub fn sample<F: FnMut(&Array1<f64>) -> f64>(
+        &self,
+        mut ln_prob: F,
+        x0: &Array1<f64>,
+    ) -> Result<McmcResult, EstimatorError> {
+        let n = x0.len();
+        if n == 0 || self.walkers < 2 {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        // initial walkers
+        let mut positions = Array2::zeros((self.walkers, n));
+        let mut ln_p = Array1::zeros(self.walkers);
+        for k in 0..self.walkers {
+            let mut attempts = 0;
+            loop {
+                let x = x0.mapv(|x| {
+                    x + self.initial_spread * x.abs().max(1.0) * (2.0 * rng.gen::<f64>() - 1.0)
+                });
+                let p = ln_prob(&x);
+                if p.is_finite() {
+                    positions.row_mut(k).assign(&x);
+                    ln_p[k] = p;
+                    break;
+                }
+                attempts += 1;
+                if attempts == MAX_INIT_ATTEMPTS {
+                    return Err(EstimatorError::ZeroProbability);
+                }
+            }
+        }
+
+        let mut samples = Array3::zeros((self.steps, self.walkers, n));
+        let mut ln_probability = Array2::zeros((self.steps, self.walkers));
+        let mut accepted = Array1::<f64>::zeros(self.walkers);
+        let a = self.stretch;
+        for step in 0..self.steps {
+            for k in 0..self.walkers {
+                let mut j = rng.gen_range(0..self.walkers - 1);
+                if j >= k {
+                    j += 1;
+                }
+                let z = ((a - 1.0) * rng.gen::<f64>() + 1.0).powi(2) / a;
+                let y = &positions.row(j) + &((&positions.row(k) - &positions.row(j)) * z);
+                let p = ln_prob(&y);
+                let ln_q = (n as f64 - 1.0) * z.ln() + p - ln_p[k];
+                if p.is_finite() && ln_q > rng.gen::<f64>().ln() {
+                    positions.row_mut(k).assign(&y);
+                    ln_p[k] = p;
+                    accepted[k] += 1.0;
+                }
+            }
+            samples.index_axis_mut(Axis(0), step).assign(&positions);
+            ln_probability.row_mut(step).assign(&ln_p);
+        }
+
+        let autocorrelation_times = (0..n)
+            .map(|i| autocorrelation_time(&samples.slice(s![.., .., i]).to_owned()))
+            .collect();
+        Ok(McmcResult {
+            samples,
+            ln_probability,
+            acceptance_rates: accepted / self.steps.max(1) as f64,
+            autocorrelation_times,
+        })
+    }
+}
+
+/// Integrated autocorrelation time of a chain with shape (steps, walkers).
+///
+/// The normalized autocorrelation function is averaged over all walkers and
+/// summed up to the smallest lag `M` with `M >= 5 tau` (automatic windowing).
+fn autocorrelation_time(chain: &Array2<f64>) -> f64 {
+    let (steps, walkers) = chain.dim();
+    if steps < 2 {
+        return f64::NAN;
+    }
+    let centered: Vec<Array1<f64>> = (0..walkers)
+        .map(|k| {
+            let c = chain.column(k);
+            let mean = c.sum() / steps as f64;
+            c.mapv(|x| x - mean)
+        })
+        .collect();
+    let variances: Vec<f64> = centered.iter().map(|c| c.dot(c)).collect();
+    let acf = |lag: usize| {
+        centered
+            .iter()
+            .zip(variances.iter())
+            .map(|(c, &v)| {
+                if v > 0.0 {
+                    c.slice(s![..steps - lag]).dot(&c.slice(s![lag..])) / v
+                } else {
+                    0.0
+                }
+            })
+            .sum::<f64>()
+            / walkers as f64
+    };
+    let mut tau = 1.0;
+    for lag in 1..steps {
+        tau += 2.0 * acf(lag);
+        if lag as f64 >= AUTOCORRELATION_WINDOW * tau {
+            break;
+        }
+    }
+    tau
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    #[test]
+    fn priors() {
+        let ln_sqrt_2pi = 0.5 * (2.0 * PI).ln();
+        assert_eq!(Prior::Flat.ln_pdf(1e10), 0.0);
+        let uniform = Prior::Uniform {
+            lower: 1.0,
+            upper: 3.0,
+        };
+        assert_eq!(uniform.ln_pdf(2.0), -(2.0f64.ln()));
+        assert_eq!(uniform.ln_pdf(3.5), f64::NEG_INFINITY);
+        let normal = Prior::Normal {
+            mean: 1.0,
+            std: 2.0,
+        };
+        assert!((normal.ln_pdf(3.0) - (-0.5 - 2.0f64.ln() - ln_sqrt_2pi)).abs() < 1e-14);
+        let log_normal = Prior::LogNormal {
+            mean: 0.0,
+            std: 1.0,
+        };
+        assert!((log_normal.ln_pdf(1.0) + ln_sqrt_2pi).abs() < 1e-14);
+        assert_eq!(log_normal.ln_pdf(0.0), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn posterior() {
+        let priors = [Prior::Uniform {
+            lower: 0.0,
+            upper: 10.0,
+        }];
+        let residuals = |x: &Array1<f64>| Ok(arr1(&[x[0] - 2.0, x[0] - 4.0]));
+        let ln_p = log_posterior(residuals, &priors, 2.0);
+        let ln_prior = -(10.0f64.ln());
+        assert!((ln_p(&arr1(&[3.0])) - (ln_prior - 0.25)).abs() < 1e-14);
+        assert_eq!(ln_p(&arr1(&[-1.0])), f64::NEG_INFINITY);
+        assert_eq!(ln_p(&arr1(&[1.0, 2.0])), f64::NEG_INFINITY);
+        let failed = log_posterior(
+            |_: &Array1<f64>| Err(EstimatorError::IncompatibleInput),
+            &priors,
+            1.0,
+        );
+        assert_eq!(failed(&arr1(&[3.0])), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn gaussian() {
+        // independent normal distributions with means (1, -2) and std (0.5, 2)
+        let ln_prob =
+            |x: &Array1<f64>| -0.5 * (((x[0] - 1.0) / 0.5).powi(2) + ((x[1] + 2.0) / 2.0).powi(2));
+        let sampler = EnsembleSampler::default().steps(2000).initial_spread(0.1);
+        let result = sampler.sample(ln_prob, &arr1(&[1.0, -2.0])).unwrap();
+        assert_eq!(result.samples.dim(), (2000, 32, 2));
+        assert!(result.acceptance_rates.iter().all(|&a| a > 0.2 && a < 0.9));
+        assert!(result
+            .autocorrelation_times
+            .iter()
+            .all(|&tau| tau > 1.0 && tau < 200.0));
+
+        let samples = result.flat_samples(500, 5).unwrap();
+        assert_eq!(samples.dim(), (300 * 32, 2));
+        let mean = samples.mean_axis(Axis(0)).unwrap();
+        let std = samples.std_axis(Axis(0), 1.0);
+        assert!((mean[0] - 1.0).abs() < 0.1);
+        assert!((mean[1] + 2.0).abs() < 0.4);
+        assert!((std[0] - 0.5).abs() < 0.1);
+        assert!((std[1] - 2.0).abs() < 0.4);
+
+        let again = sampler.sample(ln_prob, &arr1(&[1.0, -2.0])).unwrap();
+        assert_eq!(result.samples, again.samples);
+    }
+
+    #[test]
+    fn flat_samples() {
+        let result = McmcResult {
+            samples: Array3::from_shape_fn((10, 2, 1), |(i, j, _)| (2 * i + j) as f64),
+            ln_probability: Array2::zeros((10, 2)),
+            acceptance_rates: Array1::zeros(2),
+            autocorrelation_times: Array1::zeros(1),
+        };
+        let samples = result.flat_samples(6, 2).unwrap();
+        assert_eq!(samples.column(0), arr1(&[12.0, 13.0, 16.0, 17.0]));
+        assert_eq!(result.flat_samples(10, 1).unwrap().dim(), (0, 1));
+        assert!(matches!(
+            result.flat_samples(11, 1),
+            Err(EstimatorError::InvalidDiscard(11, 10))
+        ));
+    }
+
+    #[test]
+    fn zero_probability() {
+        let result = EnsembleSampler::default().sample(|_| f64::NEG_INFINITY, &arr1(&[1.0]));
+        assert!(matches!(result, Err(EstimatorError::ZeroProbability)));
+        let result = EnsembleSampler::default()
+            .walkers(1)
+            .sample(|_| 0.0, &arr1(&[1.0]));
+        assert!(matches!(result, Err(EstimatorError::IncompatibleInput)));
+    }
+
+    #[test]
+    fn autocorrelation_of_constant_chain() {
+        let chain = Array2::from_elem((100, 4), 1.0);
+        assert_eq!(autocorrelation_time(&chain), 1.0);
+        assert!(autocorrelation_time(&Array2::zeros((1, 4))).is_nan());
+    }
+}
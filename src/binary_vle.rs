@@ -1,325 +1,1071 @@
-use std::{fmt::LowerExp, rc::Rc};
+use super::{DataSet, EstimatorError, Loss, Prediction, PredictionOptions};
+use crate::dataset::{join, select};
+use feos_core::{
+    Contributions, DensityInitialization, EosResult, EosUnit, EquationOfState, PhaseEquilibrium,
+    State,
+};
+use ndarray::{arr1, concatenate, Array1, Axis};
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
 
-use super::{DataSet, FitError};
-use feos_core::{Contributions, EosUnit, EquationOfState, PhaseEquilibrium, State, VLEOptions};
-use ndarray::{arr1, Array1};
-use ndarray_stats::QuantileExt;
-use quantity::{Quantity, QuantityArray1, QuantityScalar};
-
-enum CostFunction {
-    Pressure,
-    ChemicalPotential,
+/// Cost function of the binary VLE data sets.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum BinaryVLECost {
+    /// relative differences of the bubble or dew point
+    /// pressures (isothermal) or temperatures (isobaric)
+    #[default]
+    RelativeDifference,
+    /// distance of the experimental point to the phase envelope in
+    /// the plane of the mole fraction and the relative pressure
+    /// (isothermal) or temperature (isobaric); only for data sets
+    /// with the composition of a single phase
     Distance,
+    /// norm of the difference between the reduced chemical potentials of
+    /// the liquid and the vapor at the experimental temperature, pressure
+    /// and compositions; only for data sets with the compositions of both phases
+    ChemicalPotential,
+}
+
+/// Bubble or dew point of a binary mixture for given temperature or pressure.
+///
+/// Returns the pressure if a temperature is specified and the
+/// temperature if a pressure is specified.
+fn bubble_dew_point<U: EosUnit, E: EquationOfState>(
+    eos: &Rc<E>,
+    temperature_or_pressure: QuantityScalar<U>,
+    molefracs: f64,
+    tp_init: QuantityScalar<U>,
+    molefracs_init: Option<f64>,
+    bubble: bool,
+    options: PredictionOptions,
+) -> EosResult<QuantityScalar<U>>
+where
+    QuantityScalar<U>: std::fmt::Display,
+{
+    let x = arr1(&[molefracs, 1.0 - molefracs]);
+    let x_init = molefracs_init.map(|xi| arr1(&[xi, 1.0 - xi]));
+    let options = options.bubble_dew();
+    let vle = if bubble {
+        PhaseEquilibrium::bubble_point(
+            eos,
+            temperature_or_pressure,
+            &x,
+            Some(tp_init),
+            x_init.as_ref(),
+            options,
+        )
+    } else {
+        PhaseEquilibrium::dew_point(
+            eos,
+            temperature_or_pressure,
+            &x,
+            Some(tp_init),
+            x_init.as_ref(),
+            options,
+        )
+    }?;
+    Ok(
+        if temperature_or_pressure.has_unit(&U::reference_temperature()) {
+            vle.vapor().pressure(Contributions::Total)
+        } else {
+            vle.vapor().temperature
+        },
+    )
+}
+
+/// Bubble or dew points for all data points. Failed calculations are set to `NAN`
+/// and their error is stored in the status of the prediction.
+///
+/// The experimental values of the target are used as initial values.
+fn bubble_dew_points<U: EosUnit, E: EquationOfState>(
+    eos: &Rc<E>,
+    target: &QuantityArray1<U>,
+    temperature_or_pressure: &QuantityArray1<U>,
+    molefracs: &Array1<f64>,
+    molefracs_init: Option<&Array1<f64>>,
+    bubble: bool,
+    options: PredictionOptions,
+) -> Result<Prediction<U>, EstimatorError>
+where
+    QuantityScalar<U>: std::fmt::Display,
+{
+    Prediction::from_results(
+        (0..target.len()).map(|i| {
+            bubble_dew_point(
+                eos,
+                temperature_or_pressure.get(i),
+                molefracs[i],
+                target.get(i),
+                molefracs_init.map(|x| x[i]),
+                bubble,
+                options,
+            )
+        }),
+        target.get(0),
+    )
+}
+
+/// Distance of an experimental point to the bubble or dew point curve.
+///
+/// The distance is measured in the plane of the mole fraction of the first
+/// component and the pressure or temperature relative to the experimental
+/// value. The point on the curve closest to the experimental point is
+/// found by iteratively projecting onto the tangent of the curve.
+fn distance<U: EosUnit, E: EquationOfState>(
+    eos: &Rc<E>,
+    temperature_or_pressure: QuantityScalar<U>,
+    molefracs: f64,
+    target: QuantityScalar<U>,
+    bubble: bool,
+    options: PredictionOptions,
+) -> Result<f64, EstimatorError>
+where
+    QuantityScalar<U>: std::fmt::Display,
+{
+    let dx = 1e-4;
+    let tol = 1e-9;
+    let max_iter = 60;
+    let curve = |x: f64| -> Result<f64, EstimatorError> {
+        let value = bubble_dew_point(
+            eos,
+            temperature_or_pressure,
+            x,
+            target,
+            None,
+            bubble,
+            options,
+        )?;
+        Ok(value.to_reduced(target)?)
+    };
+
+    let mut x = molefracs;
+    let mut distance = f64::NAN;
+    for i in 0..max_iter {
+        let damping = match i {
+            i if i <= 2 => 0.75,
+            i if i > 25 => 0.25,
+            _ => 1.0,
+        };
+        let dx = if x + dx > 1.0 { -dx } else { dx };
+        let v1 = curve(x)?;
+        let v2 = curve(x + dx)?;
+        let mut tangent = arr1(&[dx, v2 - v1]);
+        tangent /= tangent.dot(&tangent).sqrt();
+        let deviation = arr1(&[molefracs - x, 1.0 - v1]);
+        distance = deviation.dot(&deviation).sqrt();
+        let shift = tangent[0] * tangent.dot(&deviation);
+        if shift.abs() <= tol {
+            break;
+        }
+        x = (x + damping * shift).clamp(0.0, 1.0);
+    }
+    Ok(distance)
+}
+
+/// Norm of the difference between the reduced chemical potentials
+/// of a liquid and a vapor at given temperature and pressure.
+fn chemical_potential_difference<U: EosUnit, E: EquationOfState>(
+    eos: &Rc<E>,
+    temperature: QuantityScalar<U>,
+    pressure: QuantityScalar<U>,
+    liquid_molefracs: f64,
+    vapor_molefracs: f64,
+) -> Result<f64, EstimatorError>
+where
+    QuantityScalar<U>: std::fmt::Display,
+{
+    let chemical_potential = |molefracs: f64, phase| -> Result<Array1<f64>, EstimatorError> {
+        let moles = arr1(&[molefracs, 1.0 - molefracs]) * U::reference_moles();
+        let state = State::new_npt(eos, temperature, pressure, &moles, phase)?;
+        Ok(state
+            .chemical_potential(Contributions::Total)
+            .to_reduced(U::reference_molar_energy())?)
+    };
+    let mu_liquid = chemical_potential(liquid_molefracs, DensityInitialization::Liquid)?;
+    let mu_vapor = chemical_potential(vapor_molefracs, DensityInitialization::Vapor)?;
+    Ok((mu_liquid - mu_vapor).mapv(|dmu| dmu * dmu).sum().sqrt())
+}
+
+/// Returns `true` if `temperature_or_pressure` is a temperature and
+/// `false` if it is a pressure.
+fn is_isothermal<U: EosUnit>(
+    temperature_or_pressure: &QuantityArray1<U>,
+) -> Result<bool, EstimatorError> {
+    if temperature_or_pressure.has_unit(&U::reference_temperature()) {
+        Ok(true)
+    } else if temperature_or_pressure.has_unit(&U::reference_pressure()) {
+        Ok(false)
+    } else {
+        Err(EstimatorError::NoTemperatureOrPressure)
+    }
+}
+
+/// Description of the specified variable.
+fn specification_str(isothermal: bool) -> &'static str {
+    if isothermal {
+        "temperature"
+    } else {
+        "pressure"
+    }
 }
 
-/// Store experimental vapor pressure data and compare to the equation of state.
+/// Binary VLE data at given temperature or pressure and the
+/// composition of the liquid, the vapor or both phases.
+///
+/// Shared implementation of the isothermal and isobaric data sets.
 #[derive(Clone)]
-pub struct BinaryTPx<U: EosUnit> {
-    temperature: QuantityArray1<U>,
-    pressure: QuantityArray1<U>,
-    liquid_molefracs: Array1<f64>,
+struct BinaryVLE<U: EosUnit> {
+    /// pressure (isothermal) or temperature (isobaric)
+    target: QuantityArray1<U>,
+    /// specified temperature or pressure
+    temperature_or_pressure: QuantityArray1<U>,
+    /// liquid mole fraction of the first component
+    liquid_molefracs: Option<Array1<f64>>,
+    /// vapor mole fraction of the first component
+    vapor_molefracs: Option<Array1<f64>>,
+    /// `true` if the temperature is specified
+    isothermal: bool,
+    /// number of data points
     datapoints: usize,
+    /// cost function
+    cost: BinaryVLECost,
+    /// solver options
+    options: PredictionOptions,
 }
 
-impl<U: EosUnit> BinaryTPx<U> {
-    pub fn new(
-        temperature: QuantityArray1<U>,
-        pressure: QuantityArray1<U>,
-        liquid_molefracs: Array1<f64>,
-    ) -> Result<Self, FitError> {
-        let datapoints = temperature.len();
+impl<U: EosUnit> BinaryVLE<U> {
+    fn new(
+        target: QuantityArray1<U>,
+        temperature_or_pressure: QuantityArray1<U>,
+        liquid_molefracs: Option<Array1<f64>>,
+        vapor_molefracs: Option<Array1<f64>>,
+        isothermal: bool,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature_or_pressure.len() != datapoints
+            || liquid_molefracs.iter().any(|x| x.len() != datapoints)
+            || vapor_molefracs.iter().any(|y| y.len() != datapoints)
+        {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        if is_isothermal(&temperature_or_pressure)? != isothermal
+            || is_isothermal(&target)? == isothermal
+        {
+            return Err(EstimatorError::IncompatibleInput);
+        }
         Ok(Self {
-            temperature,
-            pressure,
+            target,
+            temperature_or_pressure,
             liquid_molefracs,
+            vapor_molefracs,
+            isothermal,
             datapoints,
+            cost: BinaryVLECost::default(),
+            options,
         })
     }
 
-    fn pressure_cost<E: EquationOfState>(&self, eos: &Rc<E>) -> Result<Array1<f64>, FitError>
-    where
-        Quantity<f64, U>: std::fmt::Display,
-    {
-        let options = (VLEOptions::default(), VLEOptions::default());
+    fn cost_function(mut self, cost: BinaryVLECost) -> Result<Self, EstimatorError> {
+        let both_phases = self.liquid_molefracs.is_some() && self.vapor_molefracs.is_some();
+        match (cost, both_phases) {
+            (BinaryVLECost::Distance, true) | (BinaryVLECost::ChemicalPotential, false) => Err(
+                EstimatorError::UnsupportedCost(self.target_str().to_owned()),
+            ),
+            _ => {
+                self.cost = cost;
+                Ok(self)
+            }
+        }
+    }
 
-        // let reduced_temperatures = (0..self.datapoints)
-        //     .map(|i| (self.temperature.get(i) * tc_inv).into_value().unwrap())
-        //     .collect::<Vec<f64>>();
+    fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        if self.cost != other.cost {
+            return Err(EstimatorError::IncompatibleDataSets);
+        }
+        let join_molefracs = |x: &Option<Array1<f64>>, y: &Option<Array1<f64>>| {
+            x.as_ref()
+                .zip(y.as_ref())
+                .map(|(x, y)| concatenate(Axis(0), &[x.view(), y.view()]))
+                .transpose()
+        };
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(
+                &self.temperature_or_pressure,
+                &other.temperature_or_pressure,
+            )?,
+            join_molefracs(&self.liquid_molefracs, &other.liquid_molefracs)?,
+            join_molefracs(&self.vapor_molefracs, &other.vapor_molefracs)?,
+            self.isothermal,
+            self.options,
+        )?
+        .cost_function(self.cost)
+    }
 
-        // let prediction = &self.predict(eos)?;
-        let mut cost = Array1::zeros(self.datapoints);
-        for i in 0..self.datapoints {
-            let xi = self.liquid_molefracs[i];
-            let prediction = PhaseEquilibrium::bubble_point_tx(
-                eos,
-                self.temperature.get(i),
-                Some(self.pressure.get(i)),
-                &arr1(&vec![xi, 1.0 - xi]),
-                None,
-                options,
-            )?
-            .vapor()
-            .pressure(Contributions::Total);
+    fn subset(&self, indices: &[usize]) -> Result<Self, EstimatorError> {
+        let select_molefracs =
+            |x: &Option<Array1<f64>>| x.as_ref().map(|x| x.select(Axis(0), indices));
+        Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature_or_pressure, indices)?,
+            select_molefracs(&self.liquid_molefracs),
+            select_molefracs(&self.vapor_molefracs),
+            self.isothermal,
+            self.options,
+        )?
+        .cost_function(self.cost)
+    }
 
-            cost[i] = ((self.pressure.get(i) - prediction) / self.pressure.get(i)).into_value()?
+    fn target_str(&self) -> &str {
+        match (
+            &self.liquid_molefracs,
+            &self.vapor_molefracs,
+            self.isothermal,
+        ) {
+            (Some(_), Some(_), true) => "pressure",
+            (Some(_), Some(_), false) => "temperature",
+            (Some(_), None, true) => "bubble point pressure",
+            (Some(_), None, false) => "bubble point temperature",
+            (None, _, true) => "dew point pressure",
+            (None, _, false) => "dew point temperature",
         }
-        Ok(cost)
     }
 
-    fn distance_cost<E: EquationOfState>(&self, eos: &Rc<E>) -> Result<Array1<f64>, FitError>
+    fn input_str(&self) -> Vec<&str> {
+        let mut input = vec![specification_str(self.isothermal)];
+        if self.liquid_molefracs.is_some() {
+            input.push("liquid molefracs");
+        }
+        if self.vapor_molefracs.is_some() {
+            input.push("vapor molefracs");
+        }
+        input
+    }
+
+    /// Returns the bubble points if the liquid composition is
+    /// known and the dew points otherwise.
+    fn predict_with_status<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<Prediction<U>, EstimatorError>
     where
-        Quantity<f64, U>: std::fmt::Display,
+        QuantityScalar<U>: std::fmt::Display,
     {
-        let dx = 1e-4;
-        let tol = 1e-9;
-        let max_iter = 60;
-        let options = (VLEOptions::default(), VLEOptions::default());
-        let mut cost = Array1::zeros(self.datapoints);
-
-        for i in 0..self.datapoints {
-            let xi = self.liquid_molefracs[i];
-            let mut dxi = if xi < 0.5 { dx } else { -dx };
-            let temperature = self.temperature.get(i);
-            let pressure = self.pressure.get(i);
-            let mut shift = 0.0;
-            'iteration: for i in 0..max_iter {
-                let damping = match i {
-                    i if i <= 2 => 0.75,
-                    i if i > 8 && shift < 1e-5 => 0.5,
-                    i if i > 25 => 0.25,
-                    _ => 1.0,
-                };
+        match (&self.liquid_molefracs, &self.vapor_molefracs) {
+            (Some(x), y) => bubble_dew_points(
+                eos,
+                &self.target,
+                &self.temperature_or_pressure,
+                x,
+                y.as_ref(),
+                true,
+                self.options,
+            ),
+            (None, Some(y)) => bubble_dew_points(
+                eos,
+                &self.target,
+                &self.temperature_or_pressure,
+                y,
+                None,
+                false,
+                self.options,
+            ),
+            (None, None) => unreachable!(),
+        }
+    }
 
-                let xi_f = xi + shift * damping;
-                let prediction = PhaseEquilibrium::bubble_point_tx(
+    /// Returns the unnormalized residuals of the selected cost function.
+    ///
+    /// For the relative difference of data sets with the compositions of
+    /// both phases, the residuals of the bubble points are followed by those
+    /// of the dew points. Failed calculations are set to `NAN`.
+    fn residuals<E: EquationOfState>(&self, eos: &Rc<E>) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let relative_difference = |prediction: QuantityArray1<U>| {
+            ((prediction - &self.target) / &self.target).into_value()
+        };
+        let residuals = match (self.cost, &self.liquid_molefracs, &self.vapor_molefracs) {
+            (BinaryVLECost::RelativeDifference, Some(x), Some(y)) => {
+                let dew_points = bubble_dew_points(
                     eos,
-                    temperature,
-                    Some(pressure),
-                    &arr1(&vec![xi_f, 1.0 - xi_f]),
-                    None,
-                    options,
-                );
-                if prediction.is_err() {
-                    cost[i] = 10.0;
-                    break 'iteration;
-                }
-                let p1 = prediction.unwrap().vapor().pressure(Contributions::Total);
-
-                if xi_f > 1.0 - dxi {
-                    dxi *= -1.0
+                    &self.target,
+                    &self.temperature_or_pressure,
+                    y,
+                    Some(x),
+                    false,
+                    self.options,
+                )?;
+                concatenate![
+                    Axis(0),
+                    relative_difference(self.predict_with_status(eos)?.values)?,
+                    relative_difference(dew_points.values)?
+                ]
+            }
+            (BinaryVLECost::RelativeDifference, _, _) => {
+                relative_difference(self.predict_with_status(eos)?.values)?
+            }
+            (BinaryVLECost::Distance, x, y) => {
+                let (molefracs, bubble) = match (x, y) {
+                    (Some(x), _) => (x, true),
+                    (None, Some(y)) => (y, false),
+                    (None, None) => unreachable!(),
                 };
-
-                let xi_b = xi_f + dxi;
-                let prediction = PhaseEquilibrium::bubble_point_tx(
-                    eos,
-                    temperature,
-                    Some(pressure),
-                    &arr1(&vec![xi_b, 1.0 - xi_b]),
-                    None,
-                    options,
-                );
-                if prediction.is_err() {
-                    cost[i] = 10.0;
-                    break 'iteration;
-                }
-                let p2 = prediction.unwrap().vapor().pressure(Contributions::Total);
-                let mut line_vec = arr1(&[dxi, (p2 - p1).to_reduced(pressure)?]);
-                line_vec /= line_vec.mapv(|li| li * li).sum().sqrt();
-                let exp_vec = arr1(&[xi - xi_f, (pressure - p1).to_reduced(pressure)?]);
-                cost[i] = (&exp_vec * &exp_vec).sum().sqrt();
-                shift = line_vec[0] * (&line_vec * &exp_vec).sum().sqrt();
-                if shift > xi_f {
-                    shift = xi_f
-                }
-                if shift < -xi_f {
-                    shift = -xi_f
-                }
-                if shift.abs() <= tol {
-                    break 'iteration;
-                }
+                (0..self.datapoints)
+                    .map(|i| {
+                        distance(
+                            eos,
+                            self.temperature_or_pressure.get(i),
+                            molefracs[i],
+                            self.target.get(i),
+                            bubble,
+                            self.options,
+                        )
+                        .unwrap_or(f64::NAN)
+                    })
+                    .collect()
             }
+            (BinaryVLECost::ChemicalPotential, Some(x), Some(y)) => (0..self.datapoints)
+                .map(|i| {
+                    let (t, p) = if self.isothermal {
+                        (self.temperature_or_pressure.get(i), self.target.get(i))
+                    } else {
+                        (self.target.get(i), self.temperature_or_pressure.get(i))
+                    };
+                    chemical_potential_difference(eos, t, p, x[i], y[i]).unwrap_or(f64::NAN)
+                })
+                .collect(),
+            (BinaryVLECost::ChemicalPotential, _, _) => unreachable!(),
+        };
+        Ok(residuals)
+    }
+
+    fn n_residuals(&self) -> usize {
+        match (self.cost, &self.liquid_molefracs, &self.vapor_molefracs) {
+            (BinaryVLECost::RelativeDifference, Some(_), Some(_)) => 2 * self.datapoints,
+            _ => self.datapoints,
         }
-        Ok(cost)
     }
-}
 
-impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for BinaryTPx<U>
-where
-    Quantity<f64, U>: std::fmt::Display + LowerExp,
-{
-    fn cost(&self, eos: &Rc<E>) -> Result<Array1<f64>, FitError> {
-        self.pressure_cost(eos)
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(3);
+        m.insert(
+            specification_str(self.isothermal).to_owned(),
+            self.temperature_or_pressure.clone(),
+        );
+        if let Some(x) = &self.liquid_molefracs {
+            m.insert("liquid molefracs".to_owned(), x.clone().into());
+        }
+        if let Some(y) = &self.vapor_molefracs {
+            m.insert("vapor molefracs".to_owned(), y.clone().into());
+        }
+        m
     }
 }
 
+/// Implement [DataSet] for a wrapper of [BinaryVLE].
+macro_rules! impl_binary_vle {
+    ($name:ident) => {
+        impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for $name<U> {
+            fn target(&self) -> QuantityArray1<U> {
+                self.0.target.clone()
+            }
+
+            fn target_str(&self) -> &str {
+                self.0.target_str()
+            }
+
+            fn input_str(&self) -> Vec<&str> {
+                self.0.input_str()
+            }
+
+            fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+            where
+                QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+            {
+                Ok(self.0.predict_with_status(eos)?.values)
+            }
+
+            fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+            where
+                QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+            {
+                self.0.predict_with_status(eos)
+            }
+
+            fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+            where
+                QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+            {
+                let mut cost = self.0.residuals(eos)?;
+                loss.apply(&mut cost.view_mut());
+                Ok(cost / self.0.datapoints as f64)
+            }
+
+            fn n_residuals(&self) -> usize {
+                self.0.n_residuals()
+            }
+
+            fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+            where
+                U: 'static,
+                E: 'static,
+            {
+                Ok(Rc::new(Self(self.0.subset(indices)?)))
+            }
+
+            fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+                self.0.get_input()
+            }
+        }
+    };
+}
+
+/// Store experimental binary VLE data at given temperature and
+/// liquid composition (bubble point pressures).
 #[derive(Clone)]
-pub struct BinaryTPy<U: EosUnit> {
-    temperature: QuantityArray1<U>,
-    pressure: QuantityArray1<U>,
-    vapor_molefracs: Array1<f64>,
-    datapoints: usize,
+pub struct BinaryTPx<U: EosUnit>(BinaryVLE<U>);
+
+impl<U: EosUnit> BinaryTPx<U> {
+    /// Create a new data set for bubble point pressures of a binary mixture.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature: QuantityArray1<U>,
+        liquid_molefracs: Array1<f64>,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        BinaryVLE::new(
+            target,
+            temperature,
+            Some(liquid_molefracs),
+            None,
+            true,
+            options,
+        )
+        .map(Self)
+    }
+
+    /// Select the cost function. [BinaryVLECost::ChemicalPotential] is not supported.
+    pub fn cost_function(self, cost: BinaryVLECost) -> Result<Self, EstimatorError> {
+        self.0.cost_function(cost).map(Self)
+    }
+
+    /// Returns temperature of data points.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.0.temperature_or_pressure.clone()
+    }
+
+    /// Returns liquid mole fractions of the first component.
+    pub fn liquid_molefracs(&self) -> Array1<f64> {
+        self.0.liquid_molefracs.clone().unwrap()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        self.0.concatenate(&other.0).map(Self)
+    }
 }
 
+impl_binary_vle!(BinaryTPx);
+
+/// Store experimental binary VLE data at given temperature and
+/// vapor composition (dew point pressures).
+#[derive(Clone)]
+pub struct BinaryTPy<U: EosUnit>(BinaryVLE<U>);
+
 impl<U: EosUnit> BinaryTPy<U> {
+    /// Create a new data set for dew point pressures of a binary mixture.
     pub fn new(
+        target: QuantityArray1<U>,
         temperature: QuantityArray1<U>,
-        pressure: QuantityArray1<U>,
         vapor_molefracs: Array1<f64>,
-    ) -> Result<Self, FitError> {
-        let datapoints = temperature.len();
-        Ok(Self {
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        BinaryVLE::new(
+            target,
             temperature,
-            pressure,
-            vapor_molefracs,
-            datapoints,
-        })
+            None,
+            Some(vapor_molefracs),
+            true,
+            options,
+        )
+        .map(Self)
     }
 
-    fn pressure_cost<E: EquationOfState>(&self, eos: &Rc<E>) -> Result<Array1<f64>, FitError>
-    where
-        Quantity<f64, U>: std::fmt::Display,
-    {
-        let options = (VLEOptions::default(), VLEOptions::default());
+    /// Select the cost function. [BinaryVLECost::ChemicalPotential] is not supported.
+    pub fn cost_function(self, cost: BinaryVLECost) -> Result<Self, EstimatorError> {
+        self.0.cost_function(cost).map(Self)
+    }
 
-        let mut cost = Array1::zeros(self.datapoints);
-        for i in 0..self.datapoints {
-            let yi = self.vapor_molefracs[i];
-            let prediction = PhaseEquilibrium::dew_point_tx(
-                eos,
-                self.temperature.get(i),
-                Some(self.pressure.get(i)),
-                &arr1(&vec![yi, 1.0 - yi]),
-                None,
-                options,
-            )?
-            .vapor()
-            .pressure(Contributions::Total);
+    /// Returns temperature of data points.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.0.temperature_or_pressure.clone()
+    }
 
-            cost[i] = ((self.pressure.get(i) - prediction) / self.pressure.get(i)).into_value()?
-        }
-        Ok(cost)
+    /// Returns vapor mole fractions of the first component.
+    pub fn vapor_molefracs(&self) -> Array1<f64> {
+        self.0.vapor_molefracs.clone().unwrap()
     }
-}
 
-impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for BinaryTPy<U>
-where
-    Quantity<f64, U>: std::fmt::Display + LowerExp,
-{
-    fn cost(&self, eos: &Rc<E>) -> Result<Array1<f64>, FitError> {
-        self.pressure_cost(eos)
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        self.0.concatenate(&other.0).map(Self)
     }
 }
 
+impl_binary_vle!(BinaryTPy);
+
+/// Store experimental binary VLE data at given temperature and
+/// compositions of both phases (pressures).
+///
+/// The cost function contains the relative differences of the
+/// bubble point pressures, followed by those of the dew point
+/// pressures, i.e., it has twice as many entries as the data set
+/// has data points.
 #[derive(Clone)]
-pub struct BinaryTPxy<U: EosUnit> {
-    temperature: QuantityArray1<U>,
-    pressure: QuantityArray1<U>,
-    liquid_molefracs: Array1<f64>,
-    vapor_molefracs: Array1<f64>,
-    datapoints: usize,
-}
+pub struct BinaryTPxy<U: EosUnit>(BinaryVLE<U>);
 
 impl<U: EosUnit> BinaryTPxy<U> {
+    /// Create a new data set for pressures of a binary mixture
+    /// with known compositions of both phases.
     pub fn new(
+        target: QuantityArray1<U>,
         temperature: QuantityArray1<U>,
-        pressure: QuantityArray1<U>,
         liquid_molefracs: Array1<f64>,
         vapor_molefracs: Array1<f64>,
-    ) -> Result<Self, FitError> {
-        let datapoints = temperature.len();
-        Ok(Self {
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        BinaryVLE::new(
+            target,
             temperature,
+            Some(liquid_molefracs),
+            Some(vapor_molefracs),
+            true,
+            options,
+        )
+        .map(Self)
+    }
+
+    /// Select the cost function. [BinaryVLECost::Distance] is not supported.
+    pub fn cost_function(self, cost: BinaryVLECost) -> Result<Self, EstimatorError> {
+        self.0.cost_function(cost).map(Self)
+    }
+
+    /// Returns temperature of data points.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.0.temperature_or_pressure.clone()
+    }
+
+    /// Returns liquid mole fractions of the first component.
+    pub fn liquid_molefracs(&self) -> Array1<f64> {
+        self.0.liquid_molefracs.clone().unwrap()
+    }
+
+    /// Returns vapor mole fractions of the first component.
+    pub fn vapor_molefracs(&self) -> Array1<f64> {
+        self.0.vapor_molefracs.clone().unwrap()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        self.0.concatenate(&other.0).map(Self)
+    }
+}
+
+impl_binary_vle!(BinaryTPxy);
+
+/// Store experimental binary VLE data at given pressure and
+/// liquid composition (bubble point temperatures).
+#[derive(Clone)]
+pub struct BinaryPTx<U: EosUnit>(BinaryVLE<U>);
+
+impl<U: EosUnit> BinaryPTx<U> {
+    /// Create a new data set for bubble point temperatures of a binary mixture.
+    pub fn new(
+        target: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        liquid_molefracs: Array1<f64>,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        BinaryVLE::new(
+            target,
             pressure,
-            liquid_molefracs,
-            vapor_molefracs,
-            datapoints,
-        })
+            Some(liquid_molefracs),
+            None,
+            false,
+            options,
+        )
+        .map(Self)
     }
 
-    fn pressure_cost<E: EquationOfState>(&self, eos: &Rc<E>) -> Result<Array1<f64>, FitError>
-    where
-        Quantity<f64, U>: std::fmt::Display,
-    {
-        let options = (VLEOptions::default(), VLEOptions::default());
+    /// Select the cost function. [BinaryVLECost::ChemicalPotential] is not supported.
+    pub fn cost_function(self, cost: BinaryVLECost) -> Result<Self, EstimatorError> {
+        self.0.cost_function(cost).map(Self)
+    }
+
+    /// Returns pressure of data points.
+    pub fn pressure(&self) -> QuantityArray1<U> {
+        self.0.temperature_or_pressure.clone()
+    }
 
-        let mut cost = Array1::zeros(2 * self.datapoints);
-        for i in 0..self.datapoints {
-            let xi = self.liquid_molefracs[i];
-            let yi = self.vapor_molefracs[i];
+    /// Returns liquid mole fractions of the first component.
+    pub fn liquid_molefracs(&self) -> Array1<f64> {
+        self.0.liquid_molefracs.clone().unwrap()
+    }
 
-            let prediction_liquid = PhaseEquilibrium::bubble_point_tx(
-                eos,
-                self.temperature.get(i),
-                Some(self.pressure.get(i)),
-                &arr1(&vec![xi, 1.0 - xi]),
-                Some(&arr1(&vec![yi, 1.0 - yi])),
-                options,
-            )?
-            .vapor()
-            .pressure(Contributions::Total);
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        self.0.concatenate(&other.0).map(Self)
+    }
+}
+
+impl_binary_vle!(BinaryPTx);
+
+/// Store experimental binary VLE data at given pressure and
+/// vapor composition (dew point temperatures).
+#[derive(Clone)]
+pub struct BinaryPTy<U: EosUnit>(BinaryVLE<U>);
+
+impl<U: EosUnit> BinaryPTy<U> {
+    /// Create a new data set for dew point temperatures of a binary mixture.
+    pub fn new(
+        target: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        vapor_molefracs: Array1<f64>,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        BinaryVLE::new(
+            target,
+            pressure,
+            None,
+            Some(vapor_molefracs),
+            false,
+            options,
+        )
+        .map(Self)
+    }
+
+    /// Select the cost function. [BinaryVLECost::ChemicalPotential] is not supported.
+    pub fn cost_function(self, cost: BinaryVLECost) -> Result<Self, EstimatorError> {
+        self.0.cost_function(cost).map(Self)
+    }
+
+    /// Returns pressure of data points.
+    pub fn pressure(&self) -> QuantityArray1<U> {
+        self.0.temperature_or_pressure.clone()
+    }
 
-            let prediction_vapor = PhaseEquilibrium::dew_point_tx(
+    /// Returns vapor mole fractions of the first component.
+    pub fn vapor_molefracs(&self) -> Array1<f64> {
+        self.0.vapor_molefracs.clone().unwrap()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        self.0.concatenate(&other.0).map(Self)
+    }
+}
+
+impl_binary_vle!(BinaryPTy);
+
+/// Store experimental binary VLE data at given pressure and
+/// compositions of both phases (temperatures).
+///
+/// The cost function contains the relative differences of the
+/// bubble point temperatures, followed by those of the dew point
+/// temperatures, i.e., it has twice as many entries as the data set
+/// has data points.
+#[derive(Clone)]
+pub struct BinaryPTxy<U: EosUnit>(BinaryVLE<U>);
+
+impl<U: EosUnit> BinaryPTxy<U> {
+    /// Create a new data set for temperatures of a binary mixture
+    /// with known compositions of both phases.
+    pub fn new(
+        target: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        liquid_molefracs: Array1<f64>,
+        vapor_molefracs: Array1<f64>,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        BinaryVLE::new(
+            target,
+            pressure,
+            Some(liquid_molefracs),
+            Some(vapor_molefracs),
+            false,
+            options,
+        )
+        .map(Self)
+    }
+
+    /// Select the cost function. [BinaryVLECost::Distance] is not supported.
+    pub fn cost_function(self, cost: BinaryVLECost) -> Result<Self, EstimatorError> {
+        self.0.cost_function(cost).map(Self)
+    }
+
+    /// Returns pressure of data points.
+    pub fn pressure(&self) -> QuantityArray1<U> {
+        self.0.temperature_or_pressure.clone()
+    }
+
+    /// Returns liquid mole fractions of the first component.
+    pub fn liquid_molefracs(&self) -> Array1<f64> {
+        self.0.liquid_molefracs.clone().unwrap()
+    }
+
+    /// Returns vapor mole fractions of the first component.
+    pub fn vapor_molefracs(&self) -> Array1<f64> {
+        self.0.vapor_molefracs.clone().unwrap()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        self.0.concatenate(&other.0).map(Self)
+    }
+}
+
+impl_binary_vle!(BinaryPTxy);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feos_core::cubic::{PengRobinson, PengRobinsonParameters};
+    use quantity::si::*;
+
+    fn propane_butane() -> Rc<PengRobinson> {
+        let parameters = PengRobinsonParameters::new_simple(
+            &[369.96, 425.1],
+            &[42.5e5, 38.0e5],
+            &[0.153, 0.2],
+            &[44.0962, 58.123],
+        )
+        .unwrap();
+        Rc::new(PengRobinson::new(Rc::new(parameters)))
+    }
+
+    /// Bubble points of propane/butane at 280 K and 300 K.
+    fn bubble_points(eos: &Rc<PengRobinson>) -> (SIArray1, SIArray1, Array1<f64>, Array1<f64>) {
+        let temperature = arr1(&[280.0, 300.0]) * KELVIN;
+        let x = arr1(&[0.3, 0.6]);
+        let mut pressure = Vec::new();
+        let mut y = Vec::new();
+        for i in 0..2 {
+            let vle = PhaseEquilibrium::bubble_point(
                 eos,
-                self.temperature.get(i),
-                Some(self.pressure.get(i)),
-                &arr1(&vec![yi, 1.0 - yi]),
-                Some(&arr1(&vec![xi, 1.0 - xi])),
-                options,
-            )?
-            .vapor()
-            .pressure(Contributions::Total);
-
-            cost[i] =
-                ((self.pressure.get(i) - prediction_liquid) / self.pressure.get(i)).into_value()?;
-            cost[self.datapoints + i] =
-                ((self.pressure.get(i) - prediction_vapor) / self.pressure.get(i)).into_value()?;
+                temperature.get(i),
+                &arr1(&[x[i], 1.0 - x[i]]),
+                None,
+                None,
+                Default::default(),
+            )
+            .unwrap();
+            pressure.push(
+                vle.vapor()
+                    .pressure(Contributions::Total)
+                    .to_reduced(PASCAL)
+                    .unwrap(),
+            );
+            y.push(vle.vapor().molefracs[0]);
         }
-        Ok(cost)
+        (temperature, arr1(&pressure) * PASCAL, x, arr1(&y))
     }
 
-    fn chemical_potential_cost<E: EquationOfState>(
-        &self,
-        eos: &Rc<E>,
-    ) -> Result<Array1<f64>, FitError>
-    where
-        Quantity<f64, U>: std::fmt::Display,
-    {
-        let mut cost = Array1::zeros(self.datapoints);
-        for i in 0..self.datapoints {
-            let xi = self.liquid_molefracs[i];
-            let yi = self.vapor_molefracs[i];
-            let temperature = self.temperature.get(i);
-            let pressure = self.pressure.get(i);
-            let mu_liquid = State::new_npt(
-                eos,
-                temperature,
-                pressure,
-                &(arr1(&[xi, 1.0 - xi]) * U::reference_moles()),
-                feos_core::DensityInitialization::Liquid,
-            )?
-            .chemical_potential(Contributions::Total)
-            .to_reduced(U::reference_molar_energy())?;
+    #[test]
+    fn isothermal_and_isobaric() {
+        let eos = propane_butane();
+        let (temperature, pressure, x, y) = bubble_points(&eos);
+        let options = PredictionOptions::default();
 
-            let mu_vapor = State::new_npt(
-                eos,
-                temperature,
-                pressure,
-                &(arr1(&[yi, 1.0 - yi]) * U::reference_moles()),
-                feos_core::DensityInitialization::Vapor,
-            )?
-            .chemical_potential(Contributions::Total)
-            .to_reduced(U::reference_molar_energy())?;
-            cost[i] = (mu_liquid - mu_vapor).mapv(|dmu| dmu * dmu).sum().sqrt();
+        // bubble points at given temperature and pressure
+        let isothermal =
+            BinaryTPx::new(pressure.clone(), temperature.clone(), x.clone(), options).unwrap();
+        let isobaric =
+            BinaryPTx::new(temperature.clone(), pressure.clone(), x.clone(), options).unwrap();
+        assert_eq!(
+            DataSet::<SIUnit, PengRobinson>::target_str(&isothermal),
+            "bubble point pressure"
+        );
+        assert_eq!(
+            DataSet::<SIUnit, PengRobinson>::target_str(&isobaric),
+            "bubble point temperature"
+        );
+        for cost in [
+            isothermal.cost(&eos, Loss::Linear).unwrap(),
+            isobaric.cost(&eos, Loss::Linear).unwrap(),
+        ]
+        .iter()
+        {
+            assert!(cost.iter().all(|c| c.abs() < 1e-6));
         }
-        Ok(cost)
+
+        // dew points at given temperature and pressure
+        let dew =
+            BinaryTPy::new(pressure.clone(), temperature.clone(), y.clone(), options).unwrap();
+        let cost = dew.cost(&eos, Loss::Linear).unwrap();
+        assert!(cost.iter().all(|c| c.abs() < 1e-6));
+        let dew =
+            BinaryPTy::new(temperature.clone(), pressure.clone(), y.clone(), options).unwrap();
+        let cost = dew.cost(&eos, Loss::Linear).unwrap();
+        assert!(cost.iter().all(|c| c.abs() < 1e-6));
+
+        // bubble and dew points at given temperature and pressure
+        let data = BinaryTPxy::new(
+            pressure.clone(),
+            temperature.clone(),
+            x.clone(),
+            y.clone(),
+            options,
+        )
+        .unwrap();
+        let cost = data.cost(&eos, Loss::Linear).unwrap();
+        assert_eq!(cost.len(), 4);
+        assert_eq!(DataSet::<SIUnit, PengRobinson>::n_residuals(&data), 4);
+        assert!(cost.iter().all(|c| c.abs() < 1e-6));
+        let data = BinaryPTxy::new(temperature, pressure, x, y, options).unwrap();
+        let cost = data.cost(&eos, Loss::Linear).unwrap();
+        assert_eq!(cost.len(), 4);
+        assert!(cost.iter().all(|c| c.abs() < 1e-6));
     }
-}
 
-impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for BinaryTPxy<U>
-where
-    Quantity<f64, U>: std::fmt::Display + LowerExp,
-{
-    fn cost(&self, eos: &Rc<E>) -> Result<Array1<f64>, FitError> {
-        self.pressure_cost(eos)
+    #[test]
+    fn distance() {
+        let eos = propane_butane();
+        let (temperature, pressure, x, _) = bubble_points(&eos);
+        let options = PredictionOptions::default();
+        let data = BinaryTPx::new(pressure.clone(), temperature.clone(), x.clone(), options)
+            .unwrap()
+            .cost_function(BinaryVLECost::Distance)
+            .unwrap();
+        let cost = data.cost(&eos, Loss::Linear).unwrap();
+        assert!(cost.iter().all(|c| c.abs() < 1e-6));
+
+        // the distance is smaller than the relative deviation of the pressure
+        let shifted = BinaryTPx::new(pressure * 1.05, temperature, x, options).unwrap();
+        let relative_difference = shifted.cost(&eos, Loss::Linear).unwrap();
+        let shifted = shifted.cost_function(BinaryVLECost::Distance).unwrap();
+        let distance = shifted.cost(&eos, Loss::Linear).unwrap();
+        for (d, r) in distance.iter().zip(relative_difference.iter()) {
+            assert!(*d > 0.0 && *d < r.abs());
+        }
+    }
+
+    #[test]
+    fn chemical_potential() {
+        let eos = propane_butane();
+        let (temperature, pressure, x, y) = bubble_points(&eos);
+        let options = PredictionOptions::default();
+        for &isothermal in [true, false].iter() {
+            let data = if isothermal {
+                BinaryTPxy::new(
+                    pressure.clone(),
+                    temperature.clone(),
+                    x.clone(),
+                    y.clone(),
+                    options,
+                )
+                .unwrap()
+                .cost_function(BinaryVLECost::ChemicalPotential)
+                .unwrap()
+                .0
+            } else {
+                BinaryPTxy::new(
+                    temperature.clone(),
+                    pressure.clone(),
+                    x.clone(),
+                    y.clone(),
+                    options,
+                )
+                .unwrap()
+                .cost_function(BinaryVLECost::ChemicalPotential)
+                .unwrap()
+                .0
+            };
+            assert_eq!(data.n_residuals(), 2);
+            let residuals = data.residuals(&eos).unwrap();
+            assert!(residuals.iter().all(|r| r.abs() < 1e-6));
+        }
+
+        // the chemical potentials differ for a shifted vapor composition
+        let data = BinaryTPxy::new(pressure, temperature, x, y * 0.9, options)
+            .unwrap()
+            .cost_function(BinaryVLECost::ChemicalPotential)
+            .unwrap();
+        let cost = data.cost(&eos, Loss::Linear).unwrap();
+        assert!(cost.iter().all(|&c| c > 1e-3));
+    }
+
+    #[test]
+    fn unsupported_cost_function() {
+        let options = PredictionOptions::default();
+        let bubble = BinaryTPx::new(
+            arr1(&[1.0e5]) * PASCAL,
+            arr1(&[300.0]) * KELVIN,
+            arr1(&[0.5]),
+            options,
+        )
+        .unwrap();
+        assert!(matches!(
+            bubble.cost_function(BinaryVLECost::ChemicalPotential),
+            Err(EstimatorError::UnsupportedCost(_))
+        ));
+        let both = BinaryPTxy::new(
+            arr1(&[300.0]) * KELVIN,
+            arr1(&[1.0e5]) * PASCAL,
+            arr1(&[0.5]),
+            arr1(&[0.7]),
+            options,
+        )
+        .unwrap();
+        assert!(matches!(
+            both.cost_function(BinaryVLECost::Distance),
+            Err(EstimatorError::UnsupportedCost(_))
+        ));
+    }
+
+    #[test]
+    fn incompatible_specification() {
+        let options = PredictionOptions::default();
+        // pressure specified for an isothermal data set
+        assert!(matches!(
+            BinaryTPx::new(
+                arr1(&[300.0]) * KELVIN,
+                arr1(&[1.0e5]) * PASCAL,
+                arr1(&[0.5]),
+                options,
+            ),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+        assert!(matches!(
+            BinaryPTy::new(
+                arr1(&[300.0]) * KELVIN,
+                arr1(&[1.0]) * METER,
+                arr1(&[0.5]),
+                options,
+            ),
+            Err(EstimatorError::NoTemperatureOrPressure)
+        ));
+    }
+
+    #[test]
+    fn concatenate() {
+        let options = PredictionOptions::default();
+        let data = BinaryTPx::new(
+            arr1(&[1.0e5]) * PASCAL,
+            arr1(&[300.0]) * KELVIN,
+            arr1(&[0.5]),
+            options,
+        )
+        .unwrap();
+        let joined = data.concatenate(&data).unwrap();
+        assert_eq!(joined.liquid_molefracs(), arr1(&[0.5, 0.5]));
+        let distance = data.clone().cost_function(BinaryVLECost::Distance).unwrap();
+        assert!(matches!(
+            data.concatenate(&distance),
+            Err(EstimatorError::IncompatibleDataSets)
+        ));
     }
 }
@@ -0,0 +1,164 @@
+use super::{DataSet, EstimatorError, Loss, Prediction};
+use crate::dataset::{join, select};
+use feos_core::{DensityInitialization, EosUnit, EquationOfState, State};
+use ndarray::{arr1, Array1};
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Store experimental isothermal compressibility data.
+#[derive(Clone)]
+pub struct IsothermalCompressibility<U: EosUnit> {
+    /// isothermal compressibility
+    pub target: QuantityArray1<U>,
+    /// temperature
+    temperature: QuantityArray1<U>,
+    /// pressure
+    pressure: QuantityArray1<U>,
+    /// number of data points
+    datapoints: usize,
+    /// phase of the density iteration
+    density_initialization: DensityInitialization<U>,
+}
+
+impl<U: EosUnit> IsothermalCompressibility<U> {
+    /// Create a new data set for experimental isothermal compressibilities.
+    ///
+    /// `density_initialization` determines the phase in which the states are created.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        density_initialization: DensityInitialization<U>,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature.len() != datapoints || pressure.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        Ok(Self {
+            target,
+            temperature,
+            pressure,
+            datapoints,
+            density_initialization,
+        })
+    }
+
+    /// Return temperature.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.temperature.clone()
+    }
+
+    /// Return pressure.
+    pub fn pressure(&self) -> QuantityArray1<U> {
+        self.pressure.clone()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            join(&self.pressure, &other.pressure)?,
+            self.density_initialization,
+        )
+    }
+}
+
+impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for IsothermalCompressibility<U> {
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        "isothermal compressibility"
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        vec!["temperature", "pressure"]
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let moles = arr1(&[1.0]) * U::reference_moles();
+        Prediction::from_results(
+            (0..self.datapoints).map(|i| {
+                let t = self.temperature.get(i);
+                let p = self.pressure.get(i);
+                State::new_npt(eos, t, p, &moles, self.density_initialization)
+                    .map(|state| state.isothermal_compressibility())
+            }),
+            self.target.get(0),
+        )
+    }
+
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut cost = self.relative_difference(eos)?;
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            select(&self.pressure, indices)?,
+            self.density_initialization,
+        )?))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(2);
+        m.insert("temperature".to_owned(), self.temperature());
+        m.insert("pressure".to_owned(), self.pressure());
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane;
+    use quantity::si::*;
+
+    #[test]
+    fn finite_difference() {
+        let eos = propane();
+        let (t, p) = (250.0 * KELVIN, 1e6 * PASCAL);
+        let data = IsothermalCompressibility::new(
+            arr1(&[1e-9]) / PASCAL,
+            arr1(&[250.0]) * KELVIN,
+            arr1(&[1e6]) * PASCAL,
+            DensityInitialization::Liquid,
+        )
+        .unwrap();
+        let prediction = data.predict(&eos).unwrap().get(0);
+
+        let moles = arr1(&[1.0]) * MOL;
+        let dp = 1.0 * PASCAL;
+        let volume = |p| {
+            State::new_npt(&eos, t, p, &moles, DensityInitialization::Liquid)
+                .unwrap()
+                .volume
+        };
+        let kappa = -(volume(p + dp) - volume(p - dp)) / (2.0 * dp) / volume(p);
+        let ratio = (prediction / kappa).into_value().unwrap();
+        assert!((ratio - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,466 @@
+//! Out-of-sample validation of fitted parameters.
+//!
+//! The data points of every [`DataSet`](crate::DataSet) in an [`Estimator`] are
+//! split into a training and a validation part. A user supplied fit routine is
+//! run on the training data and the mean absolute relative difference (MARD)
+//! of the fitted equation of state is evaluated for both parts.
+use super::{Estimator, EstimatorError};
+use feos_core::{EosUnit, EquationOfState};
+use ndarray::{Array1, Array2, Axis};
+use quantity::QuantityScalar;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::rc::Rc;
+
+/// Indices of the training and validation data points of every `DataSet`.
+#[derive(Clone, Debug)]
+pub struct Split {
+    /// indices of the training data points (one list per `DataSet`)
+    pub training: Vec<Vec<usize>>,
+    /// indices of the validation data points (one list per `DataSet`)
+    pub validation: Vec<Vec<usize>>,
+}
+
+impl Split {
+    /// Randomly assign the fraction `validation` of the data points
+    /// of every `DataSet` to the validation part.
+    ///
+    /// The results are deterministic for a given seed. Returns an error if
+    /// the training or the validation part is empty.
+    pub fn random<U: EosUnit, E: EquationOfState>(
+        estimator: &Estimator<U, E>,
+        validation: f64,
+        seed: u64,
+    ) -> Result<Self, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        if !(0.0..=1.0).contains(&validation) {
+            return Err(EstimatorError::InvalidSplit(format!(
+                "the validation fraction {} is not between 0 and 1",
+                validation
+            )));
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut split = Self::empty(estimator.datasets().len());
+        for (i, d) in estimator.datasets().iter().enumerate() {
+            let n = d.datapoints();
+            let mut indices: Vec<usize> = (0..n).collect();
+            indices.shuffle(&mut rng);
+            let n_validation = (validation * n as f64).round() as usize;
+            split.validation[i] = sorted(&indices[..n_validation]);
+            split.training[i] = sorted(&indices[n_validation..]);
+        }
+        split.check()
+    }
+
+    /// Assign all data points for which the input quantity `input` is within
+    /// `lower` and `upper` (inclusive) to the training part and all other data
+    /// points to the validation part.
+    ///
+    /// Use, e.g., the temperature to test the extrapolation of the fitted
+    /// parameters to temperatures outside the training range. Returns an
+    /// error if the training or the validation part is empty.
+    pub fn by_range<U: EosUnit, E: EquationOfState>(
+        estimator: &Estimator<U, E>,
+        input: &str,
+        lower: QuantityScalar<U>,
+        upper: QuantityScalar<U>,
+    ) -> Result<Self, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut split = Self::empty(estimator.datasets().len());
+        for (i, d) in estimator.datasets().iter().enumerate() {
+            let x = d
+                .get_input()
+                .remove(input)
+                .ok_or_else(|| EstimatorError::UnknownInput(input.to_owned()))?;
+            if !x.has_unit(&lower) || !x.has_unit(&upper) {
+                return Err(EstimatorError::IncompatibleUnits(input.to_owned()));
+            }
+            for j in 0..x.len() {
+                let xj = x.get(j);
+                if lower <= xj && xj <= upper {
+                    split.training[i].push(j);
+                } else {
+                    split.validation[i].push(j);
+                }
+            }
+        }
+        split.check()
+    }
+
+    fn empty(datasets: usize) -> Self {
+        Self {
+            training: vec![Vec::new(); datasets],
+            validation: vec![Vec::new(); datasets],
+        }
+    }
+
+    /// Returns an error if the training or the validation part contains no data points.
+    fn check(self) -> Result<Self, EstimatorError> {
+        if self.training.iter().all(|i| i.is_empty()) {
+            return Err(EstimatorError::InvalidSplit(
+                "the training part contains no data points".into(),
+            ));
+        }
+        if self.validation.iter().all(|i| i.is_empty()) {
+            return Err(EstimatorError::InvalidSplit(
+                "the validation part contains no data points".into(),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// Returns an `Estimator` that only contains the training data points.
+    ///
+    /// `DataSet`s without training data points are omitted. Returns an
+    /// error if none of the `DataSet`s contains training data points.
+    pub fn training_estimator<U, E>(
+        &self,
+        estimator: &Estimator<U, E>,
+    ) -> Result<Estimator<U, E>, EstimatorError>
+    where
+        U: EosUnit + 'static,
+        E: EquationOfState + 'static,
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let data = estimator.datasets();
+        if self.training.len() != data.len() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let training = self
+            .training
+            .iter()
+            .enumerate()
+            .filter(|(_, indices)| !indices.is_empty())
+            .map(|(i, indices)| Ok((i, data[i].subset(indices)?)))
+            .collect::<Result<Vec<_>, EstimatorError>>()?;
+        if training.is_empty() {
+            return Err(EstimatorError::InvalidSplit(
+                "the training part contains no data points".into(),
+            ));
+        }
+        Ok(estimator.with_datasets(training))
+    }
+}
+
+/// Sorted copy of `indices`.
+fn sorted(indices: &[usize]) -> Vec<usize> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    indices
+}
+
+/// Random partition of the data points of every `DataSet` into `folds` parts.
+///
+/// Every part is used once as validation data while the remaining parts are
+/// used for training. The results are deterministic for a given seed.
+#[derive(Clone, Copy, Debug)]
+pub struct KFold {
+    /// number of folds
+    pub folds: usize,
+    /// seed of the random number generator
+    pub seed: u64,
+}
+
+impl Default for KFold {
+    fn default() -> Self {
+        Self { folds: 5, seed: 0 }
+    }
+}
+
+impl KFold {
+    /// Set the number of folds.
+    pub fn folds(mut self, folds: usize) -> Self {
+        self.folds = folds;
+        self
+    }
+
+    /// Set the seed of the random number generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns one `Split` per fold.
+    ///
+    /// Returns an error if there are more folds than data points in the
+    /// largest `DataSet`, because some folds would not contain validation
+    /// data points.
+    pub fn splits<U: EosUnit, E: EquationOfState>(
+        &self,
+        estimator: &Estimator<U, E>,
+    ) -> Result<Vec<Split>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        if self.folds < 2 {
+            return Err(EstimatorError::InvalidSplit(format!(
+                "at least 2 folds are required, got {}",
+                self.folds
+            )));
+        }
+        let data = estimator.datasets();
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut splits = vec![Split::empty(data.len()); self.folds];
+        for (i, d) in data.iter().enumerate() {
+            let mut indices: Vec<usize> = (0..d.datapoints()).collect();
+            indices.shuffle(&mut rng);
+            for (j, &index) in indices.iter().enumerate() {
+                for (k, split) in splits.iter_mut().enumerate() {
+                    if j % self.folds == k {
+                        split.validation[i].push(index);
+                    } else {
+                        split.training[i].push(index);
+                    }
+                }
+            }
+        }
+        for split in splits.iter_mut() {
+            split.training.iter_mut().for_each(|i| i.sort_unstable());
+            split.validation.iter_mut().for_each(|i| i.sort_unstable());
+        }
+        splits.into_iter().map(Split::check).collect()
+    }
+}
+
+/// Mean absolute relative differences of fits to the training data.
+#[derive(Clone, Debug)]
+pub struct ValidationResult {
+    /// MARD of the training data points (one row per split, one column per `DataSet`)
+    pub training: Array2<f64>,
+    /// MARD of the validation data points (one row per split, one column per `DataSet`)
+    pub validation: Array2<f64>,
+}
+
+impl ValidationResult {
+    /// Returns the validation MARD of every `DataSet` averaged over all splits.
+    ///
+    /// Splits without validation data points of a `DataSet` are ignored.
+    pub fn mean_validation(&self) -> Array1<f64> {
+        nan_mean(&self.validation)
+    }
+
+    /// Returns the training MARD of every `DataSet` averaged over all splits.
+    ///
+    /// Splits without training data points of a `DataSet` are ignored.
+    pub fn mean_training(&self) -> Array1<f64> {
+        nan_mean(&self.training)
+    }
+}
+
+/// Mean of every column ignoring `NAN` values.
+fn nan_mean(x: &Array2<f64>) -> Array1<f64> {
+    x.map_axis(Axis(0), |column| {
+        let finite: Vec<f64> = column.iter().copied().filter(|x| !x.is_nan()).collect();
+        finite.iter().sum::<f64>() / finite.len() as f64
+    })
+}
+
+/// Fit the parameters to the training data of every split and evaluate
+/// the MARD of the training and validation data of every `DataSet`.
+///
+/// `fit` returns the fitted equation of state for an `Estimator` that contains
+/// the training data. The MARD of a `DataSet` without training or validation
+/// data points in a split is `NAN`.
+pub fn cross_validate<U, E, F>(
+    estimator: &Estimator<U, E>,
+    splits: &[Split],
+    mut fit: F,
+) -> Result<ValidationResult, EstimatorError>
+where
+    U: EosUnit + 'static,
+    E: EquationOfState + 'static,
+    QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    F: FnMut(&Estimator<U, E>) -> Result<Rc<E>, EstimatorError>,
+{
+    estimator.check_subsets()?;
+    let data = estimator.datasets();
+    let mut training = Array2::from_elem((splits.len(), data.len()), f64::NAN);
+    let mut validation = Array2::from_elem((splits.len(), data.len()), f64::NAN);
+    for (k, split) in splits.iter().enumerate() {
+        if split.validation.len() != data.len() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let eos = fit(&split.training_estimator(estimator)?)?;
+        for (i, d) in data.iter().enumerate() {
+            if !split.training[i].is_empty() {
+                training[[k, i]] = d
+                    .subset(&split.training[i])?
+                    .mean_absolute_relative_difference(&eos)?;
+            }
+            if !split.validation[i].is_empty() {
+                validation[[k, i]] = d
+                    .subset(&split.validation[i])?
+                    .mean_absolute_relative_difference(&eos)?;
+            }
+        }
+    }
+    Ok(ValidationResult {
+        training,
+        validation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane_with_acentric_factor;
+    use crate::{DataSet, Loss, PredictionOptions, VaporPressure};
+    use feos_core::cubic::PengRobinson;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    /// Vapor pressures of propane at 230, 240, ..., 320 K and a data set with 3 data points.
+    fn estimator() -> Estimator<SIUnit, PengRobinson> {
+        let data: Vec<Rc<dyn DataSet<SIUnit, PengRobinson>>> = [10, 3]
+            .iter()
+            .map(|&n| {
+                let temperature =
+                    Array1::linspace(230.0, 230.0 + 10.0 * (n - 1) as f64, n) * KELVIN;
+                let options = PredictionOptions::default();
+                let data = VaporPressure::new(
+                    Array1::ones(n) * PASCAL,
+                    temperature.clone(),
+                    Default::default(),
+                    options,
+                )
+                .unwrap();
+                let target = data.predict(&propane_with_acentric_factor(0.153)).unwrap();
+                let data =
+                    VaporPressure::new(target, temperature, Default::default(), options).unwrap();
+                Rc::new(data) as Rc<dyn DataSet<SIUnit, PengRobinson>>
+            })
+            .collect();
+        Estimator::new(data, vec![1.0, 1.0], vec![Loss::Linear; 2])
+    }
+
+    fn assert_partition(split: &Split, datapoints: &[usize]) {
+        for (i, &n) in datapoints.iter().enumerate() {
+            let mut all = split.training[i].clone();
+            all.extend(&split.validation[i]);
+            all.sort_unstable();
+            assert_eq!(all, (0..n).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn random_split() {
+        let estimator = estimator();
+        let split = Split::random(&estimator, 0.3, 0).unwrap();
+        assert_partition(&split, &[10, 3]);
+        assert_eq!(split.validation[0].len(), 3);
+        assert_eq!(split.validation[1].len(), 1);
+        assert!(matches!(
+            Split::random(&estimator, 1.5, 0),
+            Err(EstimatorError::InvalidSplit(_))
+        ));
+        for validation in [0.0, 1.0] {
+            assert!(matches!(
+                Split::random(&estimator, validation, 0),
+                Err(EstimatorError::InvalidSplit(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn split_by_range() {
+        let estimator = estimator();
+        let split =
+            Split::by_range(&estimator, "temperature", 245.0 * KELVIN, 290.0 * KELVIN).unwrap();
+        assert_eq!(split.training, vec![vec![2, 3, 4, 5, 6], vec![2]]);
+        assert_eq!(split.validation, vec![vec![0, 1, 7, 8, 9], vec![0, 1]]);
+
+        let training = split.training_estimator(&estimator).unwrap();
+        let datapoints: Vec<_> = training.datasets().iter().map(|d| d.datapoints()).collect();
+        assert_eq!(datapoints, vec![5, 1]);
+
+        // the range covers all data points
+        assert!(matches!(
+            Split::by_range(&estimator, "temperature", 200.0 * KELVIN, 350.0 * KELVIN),
+            Err(EstimatorError::InvalidSplit(_))
+        ));
+        // the range covers no data points
+        assert!(matches!(
+            Split::by_range(&estimator, "temperature", 400.0 * KELVIN, 450.0 * KELVIN),
+            Err(EstimatorError::InvalidSplit(_))
+        ));
+    }
+
+    #[test]
+    fn empty_training_estimator() {
+        let estimator = estimator();
+        let split = Split {
+            training: vec![vec![], vec![]],
+            validation: vec![(0..10).collect(), (0..3).collect()],
+        };
+        assert!(matches!(
+            split.training_estimator(&estimator),
+            Err(EstimatorError::InvalidSplit(_))
+        ));
+    }
+
+    #[test]
+    fn k_fold() {
+        let estimator = estimator();
+        let splits = KFold::default().folds(3).splits(&estimator).unwrap();
+        assert_eq!(splits.len(), 3);
+        for split in splits.iter() {
+            assert_partition(split, &[10, 3]);
+            assert_eq!(split.validation[1].len(), 1);
+        }
+        for i in 0..10 {
+            let count = splits
+                .iter()
+                .filter(|s| s.validation[0].contains(&i))
+                .count();
+            assert_eq!(count, 1);
+        }
+        assert!(matches!(
+            KFold::default().folds(1).splits(&estimator),
+            Err(EstimatorError::InvalidSplit(_))
+        ));
+        assert!(matches!(
+            KFold::default().folds(11).splits(&estimator),
+            Err(EstimatorError::InvalidSplit(_))
+        ));
+    }
+
+    #[test]
+    fn validate() {
+        let estimator = estimator();
+        let splits = KFold::default().folds(5).splits(&estimator).unwrap();
+        let result = cross_validate(&estimator, &splits, |_| {
+            Ok(propane_with_acentric_factor(0.153))
+        })
+        .unwrap();
+        assert_eq!(result.validation.dim(), (5, 2));
+        // the second data set has no validation data in two of the folds
+        assert_eq!(
+            result
+                .validation
+                .column(1)
+                .iter()
+                .filter(|x| x.is_nan())
+                .count(),
+            2
+        );
+        assert!(result.mean_validation().iter().all(|&m| m < 1e-10));
+        assert!(result.mean_training().iter().all(|&m| m < 1e-10));
+
+        let result = cross_validate(&estimator, &splits, |_| {
+            Ok(propane_with_acentric_factor(0.2))
+        })
+        .unwrap();
+        assert!(result.mean_validation().iter().all(|&m| m > 1e-2));
+    }
+
+    #[test]
+    fn mean_ignoring_nan() {
+        let x = ndarray::arr2(&[[1.0, f64::NAN], [3.0, 2.0]]);
+        assert_eq!(nan_mean(&x), arr1(&[2.0, 2.0]));
+    }
+}
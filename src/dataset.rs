@@ -2,15 +2,18 @@
 //! optimization of parameters of equations of state given
 //! a `target` which can be values from experimental data or
 //! other models.
-use crate::{EstimatorError, Loss};
-use feos_core::EosUnit;
+use crate::{DeviationStatistics, EstimatorError, Loss, Prediction};
 use feos_core::EquationOfState;
+use feos_core::{EntropyScaling, EosUnit};
 use ndarray::Array1;
 use quantity::{QuantityArray1, QuantityScalar};
 use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
+/// A condition on the input quantities of a single data point.
+pub type Predicate<U> = dyn Fn(&HashMap<String, QuantityScalar<U>>) -> bool;
+
 /// Utilities for working with experimental data.
 ///
 /// Functionalities in the context of optimizations of
@@ -30,6 +33,39 @@ pub trait DataSet<U: EosUnit, E: EquationOfState> {
     where
         QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp;
 
+    /// Evaluation of the equation of state for the target quantity
+    /// together with the status of every data point.
+    ///
+    /// The default implementation marks all `NAN` predictions as failed without
+    /// a specific reason. The data sets of this crate override it to report the
+    /// error of the failed calculation.
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(Prediction::from_values(self.predict(eos)?))
+    }
+
+    /// Returns the reduced residual entropies of the data points and the
+    /// logarithms of the experimental data divided by the reference of
+    /// the entropy scaling. Failed calculations are set to `NAN`.
+    ///
+    /// Only transport property data sets support entropy scaling,
+    /// the default implementation returns an error.
+    fn entropy_scaling_data(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<(Array1<f64>, Array1<f64>), EstimatorError>
+    where
+        E: EntropyScaling<U>,
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let _ = eos;
+        Err(EstimatorError::EntropyScalingNotSupported(
+            self.target_str().to_owned(),
+        ))
+    }
+
     /// Evaluate the cost function.
     fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
     where
@@ -38,11 +74,96 @@ pub trait DataSet<U: EosUnit, E: EquationOfState> {
     /// Returns the input quantities as HashMap. The keys are the input's descriptions.
     fn get_input(&self) -> HashMap<String, QuantityArray1<U>>;
 
+    /// Returns a new data set that only contains the data points with the given indices.
+    ///
+    /// Indices can be repeated, e.g., for bootstrap resampling.
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        let _ = indices;
+        Err(EstimatorError::SubsetNotSupported(
+            self.target_str().to_owned(),
+        ))
+    }
+
+    /// Returns a new data set that only contains the data points for which
+    /// `predicate` returns `true`.
+    ///
+    /// The predicate is called with the input quantities of every data point.
+    fn filter(&self, predicate: &Predicate<U>) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        let input = self.get_input();
+        let indices: Vec<usize> = (0..self.datapoints())
+            .filter(|&i| {
+                let point = input.iter().map(|(k, v)| (k.clone(), v.get(i))).collect();
+                predicate(&point)
+            })
+            .collect();
+        if indices.is_empty() {
+            return Err(EstimatorError::EmptySelection(format!(
+                "no data point of {} satisfies the predicate",
+                self.target_str()
+            )));
+        }
+        self.subset(&indices)
+    }
+
+    /// Returns a new data set that only contains the data points for which
+    /// the input quantity `input` is within `lower` and `upper` (inclusive).
+    ///
+    /// E.g., to select data points with reduced temperatures between 0.5 and 0.9,
+    /// use `filter_range("temperature", 0.5 * tc, 0.9 * tc)`.
+    fn filter_range(
+        &self,
+        input: &str,
+        lower: QuantityScalar<U>,
+        upper: QuantityScalar<U>,
+    ) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        let x = self
+            .get_input()
+            .remove(input)
+            .ok_or_else(|| EstimatorError::UnknownInput(input.to_owned()))?;
+        if !x.has_unit(&lower) || !x.has_unit(&upper) {
+            return Err(EstimatorError::IncompatibleUnits(input.to_owned()));
+        }
+        let indices: Vec<usize> = (0..x.len())
+            .filter(|&i| {
+                let xi = x.get(i);
+                lower <= xi && xi <= upper
+            })
+            .collect();
+        if indices.is_empty() {
+            return Err(EstimatorError::EmptySelection(format!(
+                "no data point of {} with {} within the bounds",
+                self.target_str(),
+                input
+            )));
+        }
+        self.subset(&indices)
+    }
+
     /// Returns the number of experimental data points.
     fn datapoints(&self) -> usize {
         self.target().len()
     }
 
+    /// Returns the number of entries of the cost function.
+    ///
+    /// The default is one entry per data point. Data sets whose cost
+    /// contains additional entries, e.g., for compositions, override it.
+    fn n_residuals(&self) -> usize {
+        self.datapoints()
+    }
+
     /// Returns the relative difference between the equation of state and the experimental values.
     fn relative_difference(&self, eos: &Rc<E>) -> Result<Array1<f64>, EstimatorError>
     where
@@ -65,6 +186,59 @@ pub trait DataSet<U: EosUnit, E: EquationOfState> {
             .enumerate()
             .fold(0.0, |mean, (i, x)| mean + (x.abs() - mean) / (i + 1) as f64))
     }
+
+    /// Returns statistics of the relative difference between the equation of state and the experimental values.
+    ///
+    /// `tolerance` is the absolute relative difference up to which a data point is considered as well described.
+    fn statistics(&self, eos: &Rc<E>, tolerance: f64) -> Result<DeviationStatistics, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(DeviationStatistics::new(
+            &self.relative_difference(eos)?,
+            tolerance,
+        ))
+    }
+}
+
+/// Select the elements with the given indices.
+///
+/// Returns an error if `indices` is empty or contains
+/// an index that is out of bounds.
+pub(crate) fn select<U: EosUnit>(
+    quantity: &QuantityArray1<U>,
+    indices: &[usize],
+) -> Result<QuantityArray1<U>, EstimatorError> {
+    if indices.is_empty() {
+        return Err(EstimatorError::EmptySelection("no indices given".into()));
+    }
+    let n = quantity.len();
+    indices
+        .iter()
+        .map(|&i| {
+            if i < n {
+                Ok(quantity.get(i))
+            } else {
+                Err(EstimatorError::InvalidIndex(i, n))
+            }
+        })
+        .collect()
+}
+
+/// Concatenate two quantity arrays.
+///
+/// Returns an error if the arrays have different units.
+pub(crate) fn join<U: EosUnit>(
+    a: &QuantityArray1<U>,
+    b: &QuantityArray1<U>,
+) -> Result<QuantityArray1<U>, EstimatorError> {
+    if !a.has_unit(b) {
+        return Err(EstimatorError::IncompatibleDataSets);
+    }
+    Ok((0..a.len())
+        .map(|i| a.get(i))
+        .chain((0..b.len()).map(|i| b.get(i)))
+        .collect())
 }
 
 impl<U: EosUnit, E: EquationOfState> fmt::Display for dyn DataSet<U, E> {
@@ -78,3 +252,88 @@ impl<U: EosUnit, E: EquationOfState> fmt::Display for dyn DataSet<U, E> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PredictionOptions, VaporPressure};
+    use feos_core::cubic::PengRobinson;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    #[test]
+    fn select_and_join() {
+        let x = arr1(&[1.0, 2.0, 3.0]) * KELVIN;
+        let y = select(&x, &[2, 0, 2]).unwrap();
+        assert_eq!(y.to_reduced(KELVIN).unwrap(), arr1(&[3.0, 1.0, 3.0]));
+        assert!(matches!(
+            select(&x, &[]),
+            Err(EstimatorError::EmptySelection(_))
+        ));
+        assert!(matches!(
+            select(&x, &[3]),
+            Err(EstimatorError::InvalidIndex(3, 3))
+        ));
+
+        let z = join(&x, &y).unwrap();
+        assert_eq!(
+            z.to_reduced(KELVIN).unwrap(),
+            arr1(&[1.0, 2.0, 3.0, 3.0, 1.0, 3.0])
+        );
+        assert!(matches!(
+            join(&x, &(arr1(&[1.0]) * PASCAL)),
+            Err(EstimatorError::IncompatibleDataSets)
+        ));
+    }
+
+    fn vapor_pressure() -> Rc<dyn DataSet<SIUnit, PengRobinson>> {
+        let data = VaporPressure::new(
+            arr1(&[1e5, 2e5, 3e5, 4e5]) * PASCAL,
+            arr1(&[250.0, 260.0, 270.0, 280.0]) * KELVIN,
+            Default::default(),
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        Rc::new(data)
+    }
+
+    #[test]
+    fn filter() {
+        let data = vapor_pressure();
+        let filtered = data
+            .filter(&|input| input["temperature"] > 265.0 * KELVIN)
+            .unwrap();
+        assert_eq!(
+            filtered.target().to_reduced(PASCAL).unwrap(),
+            arr1(&[3e5, 4e5])
+        );
+        assert!(matches!(
+            data.filter(&|_| false),
+            Err(EstimatorError::EmptySelection(_))
+        ));
+    }
+
+    #[test]
+    fn filter_range() {
+        let data = vapor_pressure();
+        let filtered = data
+            .filter_range("temperature", 260.0 * KELVIN, 270.0 * KELVIN)
+            .unwrap();
+        assert_eq!(
+            filtered.target().to_reduced(PASCAL).unwrap(),
+            arr1(&[2e5, 3e5])
+        );
+        assert!(matches!(
+            data.filter_range("temperature", 300.0 * KELVIN, 400.0 * KELVIN),
+            Err(EstimatorError::EmptySelection(_))
+        ));
+        assert!(matches!(
+            data.filter_range("pressure", 1.0 * PASCAL, 2.0 * PASCAL),
+            Err(EstimatorError::UnknownInput(_))
+        ));
+        assert!(matches!(
+            data.filter_range("temperature", 1.0 * PASCAL, 2.0 * PASCAL),
+            Err(EstimatorError::IncompatibleUnits(_))
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+//! Fitting of entropy scaling correlations to transport property data.
+use super::{DataSet, EstimatorError};
+use crate::linalg::lstsq;
+use feos_core::{EntropyScaling, EosUnit, EquationOfState};
+use ndarray::{Array1, Array2};
+use quantity::QuantityScalar;
+use std::rc::Rc;
+
+/// Polynomial basis `1, s, s², ..., s^order` in the reduced residual
+/// entropy `s = s_res / m` per segment.
+///
+/// With `order = 3`, this is the viscosity correlation of the
+/// entropy scaling in `feos`, where `m` is the segment number of the
+/// PC-SAFT or SAFT-VR Mie parameters (use `m = 1` for other models).
+pub fn polynomial_basis(segments: f64, order: usize) -> impl Fn(f64) -> Array1<f64> {
+    move |s_res| {
+        let s = s_res / segments;
+        Array1::from_iter((0..=order as i32).map(|k| s.powi(k)))
+    }
+}
+
+/// Fit the parameters of an entropy scaling correlation by linear least squares.
+///
+/// The correlation has to be linear in its parameters, i.e.
+/// `ln(Y / Y_ref) = sum_k c_k f_k(s_res)`, where `basis` returns the values
+/// of the functions `f_k` for a reduced residual entropy `s_res`, e.g.,
+/// [polynomial_basis].
+/// The residual entropies are computed once with the fixed
+/// equation of state. Data points for which the state cannot be
+/// computed are ignored.
+///
+/// Returns the parameters `c_k`.
+pub fn fit_entropy_scaling<U, E, D, F>(
+    dataset: &D,
+    eos: &Rc<E>,
+    basis: F,
+) -> Result<Array1<f64>, EstimatorError>
+where
+    U: EosUnit,
+    E: EquationOfState + EntropyScaling<U>,
+    D: DataSet<U, E> + ?Sized,
+    F: Fn(f64) -> Array1<f64>,
+    QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+{
+    let (s_res, ln_y) = dataset.entropy_scaling_data(eos)?;
+    let points: Vec<_> = s_res
+        .iter()
+        .zip(ln_y.iter())
+        .filter(|(s, y)| s.is_finite() && y.is_finite())
+        .collect();
+    let rows: Vec<_> = points.iter().map(|(&s, _)| basis(s)).collect();
+    let n = match rows.first() {
+        Some(row) => row.len(),
+        None => return Err(EstimatorError::RankDeficient),
+    };
+    let mut a = Array2::zeros((rows.len(), n));
+    for (mut a_i, row) in a.rows_mut().into_iter().zip(rows.iter()) {
+        if row.len() != n {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        a_i.assign(row);
+    }
+    let b = Array1::from_iter(points.iter().map(|(_, &y)| y));
+    lstsq(&a, &b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::PropaneEntropyScaling;
+    use crate::{PredictionOptions, VaporPressure, Viscosity};
+    use feos_core::DensityInitialization;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    #[test]
+    fn basis() {
+        let basis = polynomial_basis(2.0, 3);
+        assert_eq!(basis(-1.0), arr1(&[1.0, -0.5, 0.25, -0.125]));
+        assert_eq!(polynomial_basis(1.0, 0)(-1.0), arr1(&[1.0]));
+    }
+
+    #[test]
+    fn fit_viscosity() {
+        let eos = PropaneEntropyScaling::new([0.1, -1.0, -2.0]);
+        let temperature = arr1(&[200.0, 250.0, 300.0, 350.0]) * KELVIN;
+        let pressure = arr1(&[1e5, 1e6, 5e6, 1e5]) * PASCAL;
+        let data = Viscosity::new(
+            arr1(&[1.0; 4]) * PASCAL * SECOND,
+            temperature.clone(),
+            pressure.clone(),
+            DensityInitialization::Liquid,
+        )
+        .unwrap();
+        let target = data.predict(&eos).unwrap();
+        let data =
+            Viscosity::new(target, temperature, pressure, DensityInitialization::Liquid).unwrap();
+
+        let dataset: Rc<dyn DataSet<SIUnit, PropaneEntropyScaling>> = Rc::new(data);
+        let c = fit_entropy_scaling(dataset.as_ref(), &eos, polynomial_basis(1.0, 2)).unwrap();
+        for (c, c0) in c.iter().zip(eos.coefficients.iter()) {
+            assert!((c - c0).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn not_supported() {
+        let eos = PropaneEntropyScaling::new([0.1, -1.0, -2.0]);
+        let data = VaporPressure::new(
+            arr1(&[1e5]) * PASCAL,
+            arr1(&[250.0]) * KELVIN,
+            Default::default(),
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            fit_entropy_scaling(&data, &eos, polynomial_basis(1.0, 2)),
+            Err(EstimatorError::EntropyScalingNotSupported(_))
+        ));
+    }
+}
@@ -1,8 +1,11 @@
 //! The [`Estimator`] struct can be used to store multiple [`DataSet`]s for convenient parameter
 //! optimization.
-use super::{DataSet, EstimatorError, Loss};
-use feos_core::EosUnit;
+use super::{
+    DataSet, DeviationStatistics, EstimatorError, FailurePenalty, FailurePolicy, Loss, Prediction,
+};
+use crate::optimization::sum_of_squares;
 use feos_core::EquationOfState;
+use feos_core::{EosError, EosUnit};
 use ndarray::{arr1, concatenate, Array1, ArrayView1, Axis};
 use quantity::QuantityArray1;
 use quantity::QuantityScalar;
@@ -17,6 +20,8 @@ pub struct Estimator<U: EosUnit, E: EquationOfState> {
     data: Vec<Rc<dyn DataSet<U, E>>>,
     weights: Vec<f64>,
     losses: Vec<Loss>,
+    policies: Vec<FailurePolicy>,
+    penalty: Option<FailurePenalty>,
 }
 
 impl<U: EosUnit, E: EquationOfState> Estimator<U, E>
@@ -28,10 +33,13 @@ where
     /// The weights are normalized and used as multiplicator when the
     /// cost function across all `DataSet`s is evaluated.
     pub fn new(data: Vec<Rc<dyn DataSet<U, E>>>, weights: Vec<f64>, losses: Vec<Loss>) -> Self {
+        let policies = vec![FailurePolicy::default(); data.len()];
         Self {
             data,
             weights,
             losses,
+            policies,
+            penalty: None,
         }
     }
 
@@ -40,28 +48,147 @@ where
         self.data.push(data.clone());
         self.weights.push(weight);
         self.losses.push(loss);
+        self.policies.push(FailurePolicy::default());
+    }
+
+    /// Set the treatment of failed data points for the `DataSet` with
+    /// the given `index` or for all `DataSet`s if `index` is `None`.
+    pub fn set_failure_policy(
+        &mut self,
+        policy: FailurePolicy,
+        index: Option<usize>,
+    ) -> Result<(), EstimatorError> {
+        match index {
+            Some(i) if i >= self.data.len() => {
+                return Err(EstimatorError::InvalidDataSet(i, self.data.len()))
+            }
+            Some(i) => self.policies[i] = policy,
+            None => self.policies.iter_mut().for_each(|p| *p = policy),
+        }
+        Ok(())
+    }
+
+    /// Set a penalty that replaces the non-finite cost of data points in
+    /// `DataSet`s with the failure policy [FailurePolicy::Propagate].
+    /// The other failure policies take precedence over the penalty.
+    ///
+    /// In [Estimator::objective], [Estimator::residuals] and in a
+    /// [MultiEstimator](crate::MultiEstimator) the penalty can depend on the
+    /// parameters. In the objective functions, it is also used if the
+    /// equation of state can not be created or a solver fails.
+    /// Errors caused by invalid input or by [FailurePolicy::Error] are
+    /// not penalized.
+    pub fn set_failure_penalty(&mut self, penalty: Option<FailurePenalty>) {
+        self.penalty = penalty;
     }
 
     /// Returns the cost of each `DataSet`.
     ///
     /// Each cost contains the inverse weight.
     pub fn cost(&self, eos: &Rc<E>) -> Result<Array1<f64>, EstimatorError> {
+        self.penalized_cost(eos, None)
+    }
+
+    /// Returns the cost of each `DataSet` with the failure penalty
+    /// evaluated for the given parameters.
+    pub(crate) fn penalized_cost(
+        &self,
+        eos: &Rc<E>,
+        parameters: Option<&Array1<f64>>,
+    ) -> Result<Array1<f64>, EstimatorError> {
+        let penalty = self.penalty.as_ref().map(|p| p.residual(parameters));
         let w = arr1(&self.weights) / self.weights.iter().sum::<f64>();
         let predictions = self
             .data
             .iter()
             .enumerate()
-            .map(|(i, d)| Ok(d.cost(eos, self.losses[i])? * w[i]))
+            .map(|(i, d)| {
+                let mut cost = d.cost(eos, self.losses[i])?;
+                match (self.policies[i], penalty) {
+                    (FailurePolicy::Propagate, Some(penalty)) => {
+                        FailurePolicy::Penalize(penalty).apply(d.as_ref(), eos, &mut cost)?
+                    }
+                    (policy, _) => policy.apply(d.as_ref(), eos, &mut cost)?,
+                }
+                Ok(cost * w[i])
+            })
             .collect::<Result<Vec<_>, EstimatorError>>()?;
         let aview: Vec<ArrayView1<f64>> = predictions.iter().map(|pi| pi.view()).collect();
         Ok(concatenate(Axis(0), &aview)?)
     }
 
+    /// Returns the objective function for the optimizers.
+    ///
+    /// The objective function is the sum of squares of the cost for the
+    /// equation of state that `parameters` creates from a parameter vector.
+    /// Failed evaluations result in an infinite objective function. If a
+    /// failure penalty is set and the equation of state can not be created
+    /// or a solver fails, all data points are penalized instead.
+    pub fn objective<'a, F>(&'a self, parameters: F) -> impl Fn(&Array1<f64>) -> f64 + 'a
+    where
+        F: Fn(&Array1<f64>) -> Result<Rc<E>, EstimatorError> + 'a,
+    {
+        move |x| self.penalized_objective(parameters(x), x)
+    }
+
+    /// Returns the objective function for the equation of state `eos`
+    /// that was created from `parameters`.
+    pub(crate) fn penalized_objective(
+        &self,
+        eos: Result<Rc<E>, EstimatorError>,
+        parameters: &Array1<f64>,
+    ) -> f64 {
+        match eos.and_then(|eos| self.penalized_cost(&eos, Some(parameters))) {
+            Ok(cost) => sum_of_squares(Ok(cost)),
+            Err(e) => match &self.penalty {
+                Some(p) if is_eos_failure(&e) => {
+                    self.failed_objective(p.residual(Some(parameters)))
+                }
+                _ => sum_of_squares(Err(e)),
+            },
+        }
+    }
+
+    /// Objective function if every data point is penalized with `residual`.
+    fn failed_objective(&self, residual: f64) -> f64 {
+        let w_sum = self.weights.iter().sum::<f64>();
+        self.data
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(d, w)| {
+                let n = d.datapoints() as f64;
+                d.n_residuals() as f64 * (w / w_sum * residual / n).powi(2)
+            })
+            .sum()
+    }
+
+    /// Returns the cost of all `DataSet`s as function of a parameter vector.
+    ///
+    /// `parameters` creates the equation of state from the parameter vector.
+    pub fn residuals<'a, F>(
+        &'a self,
+        parameters: F,
+    ) -> impl Fn(&Array1<f64>) -> Result<Array1<f64>, EstimatorError> + 'a
+    where
+        F: Fn(&Array1<f64>) -> Result<Rc<E>, EstimatorError> + 'a,
+    {
+        move |x| self.penalized_cost(&parameters(x)?, Some(x))
+    }
+
     /// Returns the properties as computed by the equation of state for each `DataSet`.
     pub fn predict(&self, eos: &Rc<E>) -> Result<Vec<QuantityArray1<U>>, EstimatorError> {
         self.data.iter().map(|d| d.predict(eos)).collect()
     }
 
+    /// Returns the properties as computed by the equation of state together
+    /// with the status of every data point for each `DataSet`.
+    pub fn predict_with_status(&self, eos: &Rc<E>) -> Result<Vec<Prediction<U>>, EstimatorError> {
+        self.data
+            .iter()
+            .map(|d| d.predict_with_status(eos))
+            .collect()
+    }
+
     /// Returns the relative difference for each `DataSet`.
     pub fn relative_difference(&self, eos: &Rc<E>) -> Result<Vec<Array1<f64>>, EstimatorError> {
         self.data
@@ -81,11 +208,88 @@ where
             .collect()
     }
 
+    /// Returns the deviation statistics for each `DataSet`.
+    pub fn statistics(
+        &self,
+        eos: &Rc<E>,
+        tolerance: f64,
+    ) -> Result<Vec<DeviationStatistics>, EstimatorError> {
+        self.data
+            .iter()
+            .map(|d| d.statistics(eos, tolerance))
+            .collect()
+    }
+
     /// Returns the stored `DataSet`s.
     pub fn datasets(&self) -> Vec<Rc<dyn DataSet<U, E>>> {
         self.data.to_vec()
     }
 
+    /// Returns the weights of the `DataSet`s.
+    pub fn weights(&self) -> Vec<f64> {
+        self.weights.clone()
+    }
+
+    /// Returns the loss functions of the `DataSet`s.
+    pub fn losses(&self) -> Vec<Loss> {
+        self.losses.clone()
+    }
+
+    /// Returns the treatment of failed data points of the `DataSet`s.
+    pub fn failure_policies(&self) -> Vec<FailurePolicy> {
+        self.policies.clone()
+    }
+
+    /// Returns a new `Estimator` with the given `DataSet`s that uses the weight,
+    /// loss and failure policy of the `DataSet` with the given index in `self`.
+    pub(crate) fn with_datasets(&self, data: Vec<(usize, Rc<dyn DataSet<U, E>>)>) -> Self {
+        let mut estimator = Self::new(Vec::new(), Vec::new(), Vec::new());
+        for (i, d) in data {
+            estimator.data.push(d);
+            estimator.weights.push(self.weights[i]);
+            estimator.losses.push(self.losses[i]);
+            estimator.policies.push(self.policies[i]);
+        }
+        estimator.penalty = self.penalty.clone();
+        estimator
+    }
+
+    /// Returns a new `Estimator` in which every `DataSet` only contains
+    /// the data points with the given indices.
+    ///
+    /// `indices` contains one list of indices per `DataSet`.
+    pub fn subset(&self, indices: &[Vec<usize>]) -> Result<Self, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        if indices.len() != self.data.len() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let data = self
+            .data
+            .iter()
+            .zip(indices)
+            .enumerate()
+            .map(|(i, (d, indices))| Ok((i, d.subset(indices)?)))
+            .collect::<Result<Vec<_>, EstimatorError>>()?;
+        Ok(self.with_datasets(data))
+    }
+
+    /// Returns an error if a `DataSet` does not support [DataSet::subset].
+    ///
+    /// Resampling and cross validation check this before the first fit.
+    pub fn check_subsets(&self) -> Result<(), EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        for d in self.data.iter().filter(|d| d.datapoints() > 0) {
+            d.subset(&[0])?;
+        }
+        Ok(())
+    }
+
     /// Representation as markdown string.
     pub fn _repr_markdownn_(&self) -> String {
         let mut f = String::new();
@@ -104,6 +308,20 @@ where
     }
 }
 
+/// Returns `true` if the error is caused by a failure of the equation of
+/// state or one of its solvers and not by invalid input.
+fn is_eos_failure(error: &EstimatorError) -> bool {
+    match error {
+        EstimatorError::EosError(e) => !matches!(
+            e,
+            EosError::IncompatibleComponents(..)
+                | EosError::WrongUnits(..)
+                | EosError::QuantityError(_)
+        ),
+        _ => false,
+    }
+}
+
 impl<U: EosUnit, E: EquationOfState> Display for Estimator<U, E> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for d in self.data.iter() {
@@ -112,3 +330,153 @@ impl<U: EosUnit, E: EquationOfState> Display for Estimator<U, E> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane;
+    use feos_core::cubic::PengRobinson;
+    use quantity::si::*;
+    use std::collections::HashMap;
+
+    /// A data set defined outside of this crate that only
+    /// implements the required methods of [DataSet].
+    struct Custom {
+        target: SIArray1,
+        prediction: SIArray1,
+    }
+
+    impl Custom {
+        fn dataset(target: &[f64], prediction: &[f64]) -> Rc<dyn DataSet<SIUnit, PengRobinson>> {
+            Rc::new(Self {
+                target: arr1(target) * KELVIN,
+                prediction: arr1(prediction) * KELVIN,
+            })
+        }
+    }
+
+    impl DataSet<SIUnit, PengRobinson> for Custom {
+        fn target(&self) -> SIArray1 {
+            self.target.clone()
+        }
+
+        fn target_str(&self) -> &str {
+            "custom"
+        }
+
+        fn input_str(&self) -> Vec<&str> {
+            vec![]
+        }
+
+        fn predict(&self, _: &Rc<PengRobinson>) -> Result<SIArray1, EstimatorError> {
+            Ok(self.prediction.clone())
+        }
+
+        fn cost(&self, eos: &Rc<PengRobinson>, loss: Loss) -> Result<Array1<f64>, EstimatorError> {
+            let mut cost = self.relative_difference(eos)?;
+            loss.apply(&mut cost.view_mut());
+            Ok(cost / self.datapoints() as f64)
+        }
+
+        fn get_input(&self) -> HashMap<String, SIArray1> {
+            HashMap::new()
+        }
+    }
+
+    #[test]
+    fn custom_dataset() {
+        let eos = propane();
+        let estimator = Estimator::new(
+            vec![Custom::dataset(&[1.0, 2.0], &[1.5, 2.0])],
+            vec![1.0],
+            vec![Loss::Linear],
+        );
+        let cost = estimator.cost(&eos).unwrap();
+        assert_eq!(cost, arr1(&[0.25, 0.0]));
+        assert!(matches!(
+            estimator.check_subsets(),
+            Err(EstimatorError::SubsetNotSupported(_))
+        ));
+        assert!(matches!(
+            estimator.subset(&[vec![0]]),
+            Err(EstimatorError::SubsetNotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn statistics() {
+        let eos = propane();
+        let estimator = Estimator::new(
+            vec![
+                Custom::dataset(&[1.0, 2.0], &[1.5, 2.0]),
+                Custom::dataset(&[1.0, 1.0, 1.0], &[0.9, f64::NAN, 1.0]),
+            ],
+            vec![1.0, 1.0],
+            vec![Loss::Linear; 2],
+        );
+        let statistics = estimator.statistics(&eos, 0.2).unwrap();
+        assert_eq!(statistics.len(), 2);
+        assert_eq!(statistics[0].max_index, Some(0));
+        assert_eq!(statistics[0].within_tolerance, 0.5);
+        assert_eq!(statistics[1].datapoints, 3);
+        assert_eq!(statistics[1].failed, 1);
+        assert!((statistics[1].mrd + 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn failure_policy() {
+        let eos = propane();
+        let mut estimator = Estimator::new(
+            vec![
+                Custom::dataset(&[1.0, 2.0], &[1.5, f64::NAN]),
+                Custom::dataset(&[1.0], &[f64::NAN]),
+            ],
+            vec![1.0, 1.0],
+            vec![Loss::Linear; 2],
+        );
+        estimator
+            .set_failure_policy(FailurePolicy::Skip, None)
+            .unwrap();
+        estimator
+            .set_failure_policy(FailurePolicy::Penalize(4.0), Some(1))
+            .unwrap();
+        assert_eq!(estimator.cost(&eos).unwrap(), arr1(&[0.125, 0.0, 2.0]));
+        assert!(matches!(
+            estimator.set_failure_policy(FailurePolicy::Skip, Some(2)),
+            Err(EstimatorError::InvalidDataSet(2, 2))
+        ));
+    }
+
+    #[test]
+    fn failure_penalty() {
+        let eos = propane();
+        let mut estimator = Estimator::new(
+            vec![
+                Custom::dataset(&[1.0, 2.0], &[1.5, f64::NAN]),
+                Custom::dataset(&[1.0], &[f64::NAN]),
+            ],
+            vec![1.0, 1.0],
+            vec![Loss::Linear; 2],
+        );
+        estimator
+            .set_failure_policy(FailurePolicy::Skip, Some(1))
+            .unwrap();
+        estimator.set_failure_penalty(Some(FailurePenalty::new(2.0).distance(arr1(&[1.0]), 4.0)));
+
+        // the failure policy takes precedence over the penalty
+        assert_eq!(estimator.cost(&eos).unwrap(), arr1(&[0.125, 0.5, 0.0]));
+        let residuals = estimator.residuals(|_| Ok(eos.clone()));
+        assert_eq!(residuals(&arr1(&[3.0])).unwrap(), arr1(&[0.125, 2.5, 0.0]));
+        let objective = estimator.objective(|_| Ok(eos.clone()));
+        assert_eq!(objective(&arr1(&[1.0])), 0.265625);
+
+        // failed solvers penalize all data points
+        let objective =
+            estimator.objective(|_| Err(EosError::NotConverged("test".to_owned()).into()));
+        assert_eq!(objective(&arr1(&[1.0])), 1.5);
+        // errors caused by invalid input are not penalized
+        let objective = estimator.objective(|_| Err(EosError::IncompatibleComponents(1, 2).into()));
+        assert_eq!(objective(&arr1(&[1.0])), f64::INFINITY);
+        assert!(!is_eos_failure(&EstimatorError::InvalidDataSet(2, 2)));
+    }
+}
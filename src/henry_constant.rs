@@ -0,0 +1,230 @@
+use super::{DataSet, EstimatorError, Loss, Prediction, PredictionOptions};
+use crate::dataset::{join, select};
+use feos_core::{Contributions, EosUnit, EquationOfState, PhaseEquilibrium, State};
+use ndarray::Array1;
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Henry's law constants of a solute in a solvent as function of temperature.
+#[derive(Clone)]
+pub struct HenryConstant<U: EosUnit> {
+    /// Henry's law constant
+    pub target: QuantityArray1<U>,
+    /// temperature
+    temperature: QuantityArray1<U>,
+    /// index of the solute in the equation of state
+    solute: usize,
+    /// index of the solvent in the equation of state
+    solvent: usize,
+    /// number of data points
+    datapoints: usize,
+    /// solver options
+    options: PredictionOptions,
+}
+
+impl<U: EosUnit> HenryConstant<U> {
+    /// Create a new data set for Henry's law constants.
+    ///
+    /// The Henry's law constant is computed from the fugacity
+    /// coefficient of the solute at infinite dilution in the
+    /// saturated liquid of the pure solvent.
+    /// `solute` and `solvent` are the indices of the components
+    /// in the equation of state.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature: QuantityArray1<U>,
+        solute: usize,
+        solvent: usize,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        Ok(Self {
+            target,
+            temperature,
+            solute,
+            solvent,
+            datapoints,
+            options,
+        })
+    }
+
+    /// Returns temperature of data points.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.temperature.clone()
+    }
+
+    /// Returns the indices of solute and solvent.
+    pub fn components(&self) -> (usize, usize) {
+        (self.solute, self.solvent)
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        if self.solute != other.solute || self.solvent != other.solvent {
+            return Err(EstimatorError::IncompatibleDataSets);
+        }
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            self.solute,
+            self.solvent,
+            self.options,
+        )
+    }
+}
+
+/// Henry's law constant of the solute in the saturated liquid of the solvent.
+fn henry_constant<U: EosUnit, E: EquationOfState>(
+    eos: &Rc<E>,
+    solvent_eos: &Rc<E>,
+    solute: usize,
+    moles: &QuantityArray1<U>,
+    temperature: QuantityScalar<U>,
+    options: PredictionOptions,
+) -> Result<QuantityScalar<U>, EstimatorError>
+where
+    QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+{
+    let vle = PhaseEquilibrium::pure(solvent_eos, temperature, None, options.phase_equilibrium)?;
+    let liquid = vle.liquid();
+    let volume = moles.sum() / liquid.density;
+    let state = State::new_nvt(eos, temperature, volume, moles)?;
+    Ok(state.ln_phi()[solute].exp() * liquid.pressure(Contributions::Total))
+}
+
+impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for HenryConstant<U> {
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        "Henry's law constant"
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        vec!["temperature"]
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let n = eos.components();
+        for &i in [self.solute, self.solvent].iter() {
+            if i >= n {
+                return Err(EstimatorError::InvalidComponent(i, n));
+            }
+        }
+        let solvent_eos = Rc::new(eos.subset(&[self.solvent]));
+        let mut moles = Array1::zeros(n);
+        moles[self.solvent] = 1.0;
+        let moles = moles * U::reference_moles();
+
+        Prediction::from_results(
+            (0..self.datapoints).map(|i| {
+                let t = self.temperature.get(i);
+                henry_constant(eos, &solvent_eos, self.solute, &moles, t, self.options)
+            }),
+            U::reference_pressure(),
+        )
+    }
+
+    /// The cost is the difference of the logarithms of
+    /// predicted and experimental Henry's law constants.
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let prediction = self.predict(eos)?;
+        let mut cost = (prediction / &self.target).into_value()?.mapv(f64::ln);
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            self.solute,
+            self.solvent,
+            self.options,
+        )?))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(1);
+        m.insert("temperature".to_owned(), self.temperature());
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane_mixture;
+    use feos_core::SolverOptions;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    #[test]
+    fn identical_components() {
+        // For two identical components, the Henry's law constant is
+        // the fugacity of the saturated pure component.
+        let eos = propane_mixture();
+        let temperature = arr1(&[250.0, 300.0]) * KELVIN;
+        let data = HenryConstant::new(
+            arr1(&[1e6, 1e6]) * PASCAL,
+            temperature.clone(),
+            0,
+            1,
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        let prediction = data.predict(&eos).unwrap();
+        let pure = Rc::new(eos.subset(&[0]));
+        for i in 0..2 {
+            let vle =
+                PhaseEquilibrium::pure(&pure, temperature.get(i), None, SolverOptions::default())
+                    .unwrap();
+            let vapor = vle.vapor();
+            let fugacity = vapor.ln_phi()[0].exp() * vapor.pressure(Contributions::Total);
+            let ratio = (prediction.get(i) / fugacity).into_value().unwrap();
+            assert!((ratio - 1.0).abs() < 1e-6);
+        }
+
+        let data = HenryConstant::new(prediction, temperature, 0, 1, PredictionOptions::default())
+            .unwrap();
+        let cost = data.cost(&eos, Loss::Linear).unwrap();
+        assert!(cost.iter().all(|c| c.abs() < 1e-10));
+    }
+
+    #[test]
+    fn concatenate() {
+        let t = arr1(&[250.0]) * KELVIN;
+        let h = arr1(&[1e6]) * PASCAL;
+        let options = PredictionOptions::default();
+        let a = HenryConstant::new(h.clone(), t.clone(), 0, 1, options).unwrap();
+        let b = HenryConstant::new(h.clone(), t.clone(), 0, 1, options).unwrap();
+        let c = HenryConstant::new(h, t, 1, 0, options).unwrap();
+        assert_eq!(a.concatenate(&b).unwrap().datapoints, 2);
+        assert!(matches!(
+            a.concatenate(&c),
+            Err(EstimatorError::IncompatibleDataSets)
+        ));
+    }
+}
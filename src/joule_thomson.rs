@@ -0,0 +1,182 @@
+use super::{DataSet, EstimatorError, Loss, Prediction};
+use crate::dataset::{join, select};
+use feos_core::{DensityInitialization, EosUnit, EquationOfState, State};
+use ndarray::{arr1, Array1};
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Store experimental Joule-Thomson coefficients.
+#[derive(Clone)]
+pub struct JouleThomsonCoefficient<U: EosUnit> {
+    /// Joule-Thomson coefficient
+    pub target: QuantityArray1<U>,
+    /// temperature
+    temperature: QuantityArray1<U>,
+    /// pressure
+    pressure: QuantityArray1<U>,
+    /// mean absolute value of the target used to scale the cost function
+    scale: QuantityScalar<U>,
+    /// number of data points
+    datapoints: usize,
+    /// phase of the density iteration
+    density_initialization: DensityInitialization<U>,
+}
+
+impl<U: EosUnit> JouleThomsonCoefficient<U> {
+    /// Create a new data set for experimental Joule-Thomson coefficients.
+    ///
+    /// `density_initialization` determines the phase in which the states are created.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        density_initialization: DensityInitialization<U>,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature.len() != datapoints || pressure.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let unit = U::reference_temperature() / U::reference_pressure();
+        let scale = target.to_reduced(unit)?.mapv(f64::abs).mean().unwrap() * unit;
+        Ok(Self {
+            target,
+            temperature,
+            pressure,
+            scale,
+            datapoints,
+            density_initialization,
+        })
+    }
+
+    /// Return temperature.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.temperature.clone()
+    }
+
+    /// Return pressure.
+    pub fn pressure(&self) -> QuantityArray1<U> {
+        self.pressure.clone()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            join(&self.pressure, &other.pressure)?,
+            self.density_initialization,
+        )
+    }
+}
+
+impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for JouleThomsonCoefficient<U> {
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        "Joule-Thomson coefficient"
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        vec!["temperature", "pressure"]
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let moles = arr1(&[1.0]) * U::reference_moles();
+        Prediction::from_results(
+            (0..self.datapoints).map(|i| {
+                let t = self.temperature.get(i);
+                let p = self.pressure.get(i);
+                State::new_npt(eos, t, p, &moles, self.density_initialization)
+                    .map(|state| state.joule_thomson())
+            }),
+            self.target.get(0),
+        )
+    }
+
+    /// Joule-Thomson coefficients change sign at the inversion curve.
+    /// Therefore, the cost function uses the absolute difference
+    /// between prediction and target divided by the mean absolute
+    /// value of the target instead of the relative difference.
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let prediction = self.predict(eos)?;
+        let mut cost = ((prediction - &self.target) / self.scale).into_value()?;
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            select(&self.pressure, indices)?,
+            self.density_initialization,
+        )?))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(2);
+        m.insert("temperature".to_owned(), self.temperature());
+        m.insert("pressure".to_owned(), self.pressure());
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane;
+    use quantity::si::*;
+
+    #[test]
+    fn cost_is_scaled_by_mean_absolute_target() {
+        let eos = propane();
+        let temperature = arr1(&[300.0, 350.0]) * KELVIN;
+        let pressure = arr1(&[1e5, 2e5]) * PASCAL;
+        let unit = KELVIN / PASCAL;
+        let data = JouleThomsonCoefficient::new(
+            arr1(&[1.0, 1.0]) * unit,
+            temperature.clone(),
+            pressure.clone(),
+            DensityInitialization::Vapor,
+        )
+        .unwrap();
+        let prediction = data.predict(&eos).unwrap().to_reduced(unit).unwrap();
+        assert!(prediction.iter().all(|mu| mu.is_finite()));
+
+        // one target changes sign, the cost stays finite
+        let target = arr1(&[2.0 * prediction[0], -prediction[1]]);
+        let scale = target.mapv(f64::abs).mean().unwrap();
+        let data = JouleThomsonCoefficient::new(
+            &target * unit,
+            temperature,
+            pressure,
+            DensityInitialization::Vapor,
+        )
+        .unwrap();
+        let cost = data.cost(&eos, Loss::Linear).unwrap();
+        let expected = (prediction - target) / scale / 2.0;
+        for (c, e) in cost.iter().zip(expected.iter()) {
+            assert!((c - e).abs() < 1e-10);
+        }
+    }
+}
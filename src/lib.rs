@@ -4,30 +4,121 @@ use std::num::ParseFloatError;
 use thiserror::Error;
 
 mod dataset;
-pub use dataset::DataSet;
-// mod binary_vle;
+pub use dataset::{DataSet, Predicate};
 mod estimator;
 pub use estimator::Estimator;
+mod multi_estimator;
+pub use multi_estimator::{MultiEstimator, ParameterMapping};
+mod bayesian;
+pub use bayesian::{log_posterior, EnsembleSampler, McmcResult, Prior};
+mod resampling;
+pub use resampling::{jackknife, Bootstrap, ResamplingMethod, ResamplingResult};
+mod cross_validation;
+pub use cross_validation::{cross_validate, KFold, Split, ValidationResult};
 mod loss;
 pub use loss::Loss;
+mod prediction;
+pub use prediction::{FailurePenalty, FailurePolicy, Prediction, PredictionStatus};
+mod options;
+pub use options::PredictionOptions;
+mod statistics;
+pub use statistics::DeviationStatistics;
 mod vapor_pressure;
-pub use vapor_pressure::VaporPressure;
+pub use vapor_pressure::{Extrapolation, VaporPressure};
 mod liquid_density;
-pub use liquid_density::{LiquidDensity, EquilibriumLiquidDensity};
+pub use liquid_density::{EquilibriumLiquidDensity, LiquidDensity};
 mod viscosity;
-pub use viscosity::Viscosity;
+pub use viscosity::{EquilibriumViscosity, Viscosity};
 mod thermal_conductivity;
-pub use thermal_conductivity::ThermalConductivity;
+pub use thermal_conductivity::{EquilibriumThermalConductivity, ThermalConductivity};
 mod diffusion;
-pub use diffusion::Diffusion;
+pub use diffusion::{Diffusion, EquilibriumDiffusion};
+mod entropy_scaling;
+pub use entropy_scaling::{fit_entropy_scaling, polynomial_basis};
+mod linalg;
+mod optimization;
+pub use optimization::{
+    covariance, jacobian, Bounds, DifferentialEvolution, Multistart, NelderMead,
+    OptimizationResult, ParameterTransformation, PatternSearch, Profile, ProfileLikelihood,
+    Transformation,
+};
+mod activity_coefficient;
+pub use activity_coefficient::InfiniteDilutionActivityCoefficient;
+mod henry_constant;
+pub use henry_constant::HenryConstant;
+mod azeotrope;
+pub use azeotrope::Azeotrope;
+mod binary_vle;
+pub use binary_vle::{
+    BinaryPTx, BinaryPTxy, BinaryPTy, BinaryTPx, BinaryTPxy, BinaryTPy, BinaryVLECost,
+};
+mod multicomponent_vle;
+pub use multicomponent_vle::MulticomponentVLE;
+mod joule_thomson;
+pub use joule_thomson::JouleThomsonCoefficient;
+mod compressibility;
+pub use compressibility::IsothermalCompressibility;
+mod state_property;
+pub use state_property::{StateProperty, StatePropertyDataSet};
 
 #[cfg(feature = "python")]
 pub mod python;
 
+#[cfg(test)]
+mod test_eos;
+
 #[derive(Debug, Error)]
 pub enum EstimatorError {
     #[error("Input has not the same amount of data as the target.")]
     IncompatibleInput,
+    #[error("Component index {0} is out of bounds for an equation of state with {1} components.")]
+    InvalidComponent(usize, usize),
+    #[error("Solute and solvent have to be different components, got index {0} for both.")]
+    IdenticalComponents(usize),
+    #[error("The specification has to be either a temperature or a pressure.")]
+    NoTemperatureOrPressure,
+    #[error("The specification has to be either a pressure or a density.")]
+    NoPressureOrDensity,
+    #[error("The linear least squares problem is rank deficient.")]
+    RankDeficient,
+    #[error("Lower bounds have to be smaller than or equal to upper bounds.")]
+    InvalidBounds,
+    #[error("Parameter {0} is outside the domain of its transformation.")]
+    OutOfDomain(usize),
+    #[error("No initial walkers with finite posterior probability were found.")]
+    ZeroProbability,
+    #[error("Can not discard {0} steps of a chain with {1} steps.")]
+    InvalidDiscard(usize, usize),
+    #[error("Index {0} is out of bounds for a data set with {1} data points.")]
+    InvalidIndex(usize, usize),
+    #[error("The data set for {0} does not support subsets.")]
+    SubsetNotSupported(String),
+    #[error("The data set for {0} does not support entropy scaling.")]
+    EntropyScalingNotSupported(String),
+    #[error("Percentiles are not available for jackknife results.")]
+    JackknifePercentile,
+    #[error("All {0} fits to resampled data failed.")]
+    AllFitsFailed(usize),
+    #[error("The jackknife requires at least two data sets, the estimator contains {0}.")]
+    NotEnoughDataSets(usize),
+    #[error("The selection of data points is empty: {0}.")]
+    EmptySelection(String),
+    #[error("Unknown input `{0}`.")]
+    UnknownInput(String),
+    #[error("The bounds do not have the same unit as the input `{0}`.")]
+    IncompatibleUnits(String),
+    #[error("The data sets have incompatible specifications and can not be concatenated.")]
+    IncompatibleDataSets,
+    #[error("Invalid split of the data: {0}.")]
+    InvalidSplit(String),
+    #[error("Data set index {0} is out of bounds for an estimator with {1} data sets.")]
+    InvalidDataSet(usize, usize),
+    #[error("The prediction of {0} failed for data point {1}: {2}")]
+    PredictionFailed(String, usize, String),
+    #[error("The cost function is not supported by the data set for {0}.")]
+    UnsupportedCost(String),
+    #[error("Unknown property `{0}`.")]
+    UnknownProperty(String),
     #[error(transparent)]
     ShapeError(#[from] ndarray::ShapeError),
     #[error(transparent)]
@@ -36,4 +127,7 @@ pub enum EstimatorError {
     QuantityError(#[from] QuantityError),
     #[error(transparent)]
     EosError(#[from] EosError),
+    #[cfg(feature = "python")]
+    #[error(transparent)]
+    PyError(#[from] pyo3::PyErr),
 }
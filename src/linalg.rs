@@ -0,0 +1,141 @@
+use crate::EstimatorError;
+use ndarray::{s, Array1, Array2};
+
+/// Relative tolerance for diagonal elements of the triangular factor.
+const RANK_TOL: f64 = 1e-12;
+
+/// Solve the linear least squares problem `min |a x - b|` using a
+/// Householder QR decomposition of `a`.
+pub(crate) fn lstsq(a: &Array2<f64>, b: &Array1<f64>) -> Result<Array1<f64>, EstimatorError> {
+    let (m, n) = a.dim();
+    if m < n || b.len() != m {
+        return Err(EstimatorError::RankDeficient);
+    }
+    let mut r = a.clone();
+    let mut y = b.clone();
+    for k in 0..n {
+        let mut v = r.slice(s![k.., k]).to_owned();
+        let norm = v.dot(&v).sqrt();
+        if norm == 0.0 {
+            return Err(EstimatorError::RankDeficient);
+        }
+        v[0] += if v[0] >= 0.0 { norm } else { -norm };
+        let v2 = v.dot(&v);
+        for j in k..n {
+            let f = 2.0 * v.dot(&r.slice(s![k.., j])) / v2;
+            r.slice_mut(s![k.., j]).scaled_add(-f, &v);
+        }
+        let f = 2.0 * v.dot(&y.slice(s![k..])) / v2;
+        y.slice_mut(s![k..]).scaled_add(-f, &v);
+    }
+
+    let r_max = (0..n).fold(0.0f64, |acc, i| acc.max(r[[i, i]].abs()));
+    let mut x = Array1::zeros(n);
+    for i in (0..n).rev() {
+        if r[[i, i]].abs() <= RANK_TOL * r_max {
+            return Err(EstimatorError::RankDeficient);
+        }
+        let sum = r.slice(s![i, i + 1..]).dot(&x.slice(s![i + 1..]));
+        x[i] = (y[i] - sum) / r[[i, i]];
+    }
+    Ok(x)
+}
+
+/// Invert a square matrix using Gauss-Jordan elimination with partial pivoting.
+pub(crate) fn inverse(a: &Array2<f64>) -> Result<Array2<f64>, EstimatorError> {
+    let n = a.nrows();
+    if a.ncols() != n {
+        return Err(EstimatorError::IncompatibleInput);
+    }
+    let mut a = a.clone();
+    let mut inv = Array2::eye(n);
+    let a_max = a.iter().fold(0.0f64, |acc, x| acc.max(x.abs()));
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| a[[i, k]].abs().total_cmp(&a[[j, k]].abs()))
+            .unwrap();
+        let p = a[[pivot, k]].abs();
+        if p <= RANK_TOL * a_max || p.is_nan() {
+            return Err(EstimatorError::RankDeficient);
+        }
+        for j in 0..n {
+            a.swap([k, j], [pivot, j]);
+            inv.swap([k, j], [pivot, j]);
+        }
+        let d = a[[k, k]];
+        a.row_mut(k).mapv_inplace(|x| x / d);
+        inv.row_mut(k).mapv_inplace(|x| x / d);
+        for i in 0..n {
+            if i != k {
+                let f = a[[i, k]];
+                if f != 0.0 {
+                    let (a_k, inv_k) = (a.row(k).to_owned(), inv.row(k).to_owned());
+                    a.row_mut(i).scaled_add(-f, &a_k);
+                    inv.row_mut(i).scaled_add(-f, &inv_k);
+                }
+            }
+        }
+    }
+    Ok(inv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{arr1, arr2};
+
+    #[test]
+    fn lstsq_exact() {
+        let a = arr2(&[[2.0, 1.0], [1.0, 3.0]]);
+        let x = lstsq(&a, &arr1(&[3.0, 5.0])).unwrap();
+        assert!((x[0] - 0.8).abs() < 1e-14);
+        assert!((x[1] - 1.4).abs() < 1e-14);
+    }
+
+    #[test]
+    fn lstsq_line() {
+        let a = arr2(&[[1.0, 0.0], [1.0, 1.0], [1.0, 2.0], [1.0, 3.0]]);
+        let b = arr1(&[1.1, 2.9, 5.1, 6.9]);
+        let x = lstsq(&a, &b).unwrap();
+        assert!((x[0] - 1.06).abs() < 1e-12);
+        assert!((x[1] - 1.96).abs() < 1e-12);
+    }
+
+    #[test]
+    fn lstsq_rank_deficient() {
+        let a = arr2(&[[1.0, 2.0], [2.0, 4.0], [3.0, 6.0]]);
+        assert!(matches!(
+            lstsq(&a, &arr1(&[1.0, 2.0, 3.0])),
+            Err(EstimatorError::RankDeficient)
+        ));
+        let a = arr2(&[[1.0, 2.0]]);
+        assert!(matches!(
+            lstsq(&a, &arr1(&[1.0])),
+            Err(EstimatorError::RankDeficient)
+        ));
+    }
+
+    #[test]
+    fn inverse_with_pivoting() {
+        let a = arr2(&[[0.0, 2.0, 1.0], [1.0, 1.0, 0.0], [2.0, 0.0, 3.0]]);
+        let inv = inverse(&a).unwrap();
+        let eye = a.dot(&inv);
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((eye[[i, j]] - expected).abs() < 1e-14);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_singular() {
+        let a = arr2(&[[1.0, 2.0], [2.0, 4.0]]);
+        assert!(matches!(inverse(&a), Err(EstimatorError::RankDeficient)));
+        let a = arr2(&[[1.0, 2.0]]);
+        assert!(matches!(
+            inverse(&a),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+    }
+}
@@ -1,7 +1,7 @@
-use super::{DataSet, EstimatorError, Loss};
+use super::{DataSet, EstimatorError, Loss, Prediction, PredictionOptions, PredictionStatus};
+use crate::dataset::{join, select};
 use feos_core::{
-    DensityInitialization, EosUnit, EquationOfState, MolarWeight, PhaseEquilibrium, SolverOptions,
-    State,
+    DensityInitialization, EosUnit, EquationOfState, MolarWeight, PhaseEquilibrium, State,
 };
 use ndarray::{arr1, Array1};
 use quantity::{QuantityArray1, QuantityScalar};
@@ -46,6 +46,15 @@ impl<U: EosUnit> LiquidDensity<U> {
     pub fn pressure(&self) -> QuantityArray1<U> {
         self.pressure.clone()
     }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            join(&self.pressure, &other.pressure)?,
+        )
+    }
 }
 
 impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> DataSet<U, E> for LiquidDensity<U> {
@@ -61,10 +70,21 @@ impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> DataSet<U, E> for LiquidDe
         vec!["temperature", "pressure"]
     }
 
-    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError> {
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
         let moles = arr1(&[1.0]) * U::reference_moles();
         let unit = self.target.get(0);
         let mut prediction = Array1::zeros(self.datapoints) * unit;
+        let mut status = Vec::with_capacity(self.datapoints);
         for i in 0..self.datapoints {
             let state = State::new_npt(
                 eos,
@@ -73,13 +93,21 @@ impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> DataSet<U, E> for LiquidDe
                 &moles,
                 DensityInitialization::Liquid,
             );
-            if let Ok(s) = state {
-                prediction.try_set(i, s.mass_density())?;
-            } else {
-                prediction.try_set(i, f64::NAN * unit)?;
+            match state {
+                Ok(s) => {
+                    prediction.try_set(i, s.mass_density())?;
+                    status.push(PredictionStatus::Converged);
+                }
+                Err(e) => {
+                    prediction.try_set(i, f64::NAN * unit)?;
+                    status.push(PredictionStatus::Failed(e.to_string()));
+                }
             }
         }
-        Ok(prediction)
+        Ok(Prediction {
+            values: prediction,
+            status,
+        })
     }
 
     fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
@@ -91,6 +119,18 @@ impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> DataSet<U, E> for LiquidDe
         Ok(cost / self.datapoints as f64)
     }
 
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            select(&self.pressure, indices)?,
+        )?))
+    }
+
     fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
         let mut m = HashMap::with_capacity(2);
         m.insert("temperature".to_owned(), self.temperature());
@@ -105,6 +145,7 @@ pub struct EquilibriumLiquidDensity<U: EosUnit> {
     pub target: QuantityArray1<U>,
     temperature: QuantityArray1<U>,
     datapoints: usize,
+    options: PredictionOptions,
 }
 
 impl<U: EosUnit> EquilibriumLiquidDensity<U> {
@@ -112,12 +153,14 @@ impl<U: EosUnit> EquilibriumLiquidDensity<U> {
     pub fn new(
         target: QuantityArray1<U>,
         temperature: QuantityArray1<U>,
+        options: PredictionOptions,
     ) -> Result<Self, EstimatorError> {
         let datapoints = target.len();
         Ok(Self {
             target,
             temperature,
             datapoints,
+            options,
         })
     }
 
@@ -125,6 +168,15 @@ impl<U: EosUnit> EquilibriumLiquidDensity<U> {
     pub fn temperature(&self) -> QuantityArray1<U> {
         self.temperature.clone()
     }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            self.options,
+        )
+    }
 }
 
 impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> DataSet<U, E>
@@ -143,21 +195,38 @@ impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> DataSet<U, E>
     }
 
     fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
     where
         QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
     {
         let unit = self.target.get(0);
 
         let mut prediction = Array1::zeros(self.datapoints) * unit;
+        let mut status = Vec::with_capacity(self.datapoints);
         for i in 0..self.datapoints {
             let t = self.temperature.get(i);
-            if let Ok(state) = PhaseEquilibrium::pure(eos, t, None, SolverOptions::default()) {
-                prediction.try_set(i, state.liquid().mass_density())?;
-            } else {
-                prediction.try_set(i, f64::NAN * U::reference_mass() / U::reference_volume())?
+            match PhaseEquilibrium::pure(eos, t, None, self.options.phase_equilibrium) {
+                Ok(state) => {
+                    prediction.try_set(i, state.liquid().mass_density())?;
+                    status.push(PredictionStatus::Converged);
+                }
+                Err(e) => {
+                    prediction
+                        .try_set(i, f64::NAN * U::reference_mass() / U::reference_volume())?;
+                    status.push(PredictionStatus::Failed(e.to_string()));
+                }
             }
         }
-        Ok(prediction)
+        Ok(Prediction {
+            values: prediction,
+            status,
+        })
     }
 
     fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
@@ -169,6 +238,18 @@ impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> DataSet<U, E>
         Ok(cost / self.datapoints as f64)
     }
 
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            self.options,
+        )?))
+    }
+
     fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
         let mut m = HashMap::with_capacity(2);
         m.insert("temperature".to_owned(), self.temperature());
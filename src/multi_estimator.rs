@@ -0,0 +1,210 @@
+//! The [`MultiEstimator`] struct can be used to fit parameters that are
+//! shared between multiple substances, e.g., group contribution parameters.
+use super::{Estimator, EstimatorError};
+use crate::optimization::jacobian;
+use feos_core::{EosUnit, EquationOfState};
+use ndarray::{concatenate, Array1, Array2, ArrayView1, Axis};
+use quantity::QuantityScalar;
+use std::rc::Rc;
+
+/// Function that creates the equation of state of a
+/// substance from the global parameter vector.
+pub type ParameterMapping<E> = dyn Fn(&Array1<f64>) -> Result<Rc<E>, EstimatorError>;
+
+/// A collection of [`Estimator`]s for different substances, each with
+/// its own equation of state that is created from a global parameter vector.
+pub struct MultiEstimator<U: EosUnit, E: EquationOfState> {
+    estimators: Vec<Estimator<U, E>>,
+    mappings: Vec<Box<ParameterMapping<E>>>,
+}
+
+impl<U: EosUnit, E: EquationOfState> MultiEstimator<U, E>
+where
+    QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+{
+    /// Create a new `MultiEstimator` given an `Estimator` and a
+    /// parameter mapping for each substance.
+    pub fn new(
+        estimators: Vec<Estimator<U, E>>,
+        mappings: Vec<Box<ParameterMapping<E>>>,
+    ) -> Result<Self, EstimatorError> {
+        if estimators.len() != mappings.len() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        Ok(Self {
+            estimators,
+            mappings,
+        })
+    }
+
+    /// Add the `Estimator` and the parameter mapping of a substance.
+    pub fn add_estimator(&mut self, estimator: Estimator<U, E>, mapping: Box<ParameterMapping<E>>) {
+        self.estimators.push(estimator);
+        self.mappings.push(mapping);
+    }
+
+    /// Returns the stored `Estimator`s.
+    pub fn estimators(&self) -> &[Estimator<U, E>] {
+        &self.estimators
+    }
+
+    /// Returns the equations of state of all substances for the given parameters.
+    pub fn equations_of_state(
+        &self,
+        parameters: &Array1<f64>,
+    ) -> Result<Vec<Rc<E>>, EstimatorError> {
+        self.mappings.iter().map(|m| m(parameters)).collect()
+    }
+
+    /// Returns the cost of each substance.
+    ///
+    /// The failure penalties of the `Estimator`s are evaluated for the given parameters.
+    pub fn cost(&self, parameters: &Array1<f64>) -> Result<Vec<Array1<f64>>, EstimatorError> {
+        self.estimators
+            .iter()
+            .zip(self.equations_of_state(parameters)?.iter())
+            .map(|(e, eos)| e.penalized_cost(eos, Some(parameters)))
+            .collect()
+    }
+
+    /// Returns the concatenated cost of all substances.
+    pub fn residuals(&self, parameters: &Array1<f64>) -> Result<Array1<f64>, EstimatorError> {
+        let cost = self.cost(parameters)?;
+        let aview: Vec<ArrayView1<f64>> = cost.iter().map(|c| c.view()).collect();
+        Ok(concatenate(Axis(0), &aview)?)
+    }
+
+    /// Returns the Jacobian of the concatenated cost with respect to the
+    /// parameters, evaluated by central finite differences.
+    pub fn jacobian(&self, parameters: &Array1<f64>) -> Result<Array2<f64>, EstimatorError> {
+        jacobian(|x| self.residuals(x), parameters)
+    }
+
+    /// Returns the objective function for the optimizers, i.e., the sum of
+    /// squares of the concatenated cost.
+    ///
+    /// Failed evaluations result in an infinite objective function. If a
+    /// failure penalty is set for the `Estimator` of a substance and its
+    /// equation of state can not be created or a solver fails, all data
+    /// points of the substance are penalized instead (see [Estimator::objective]).
+    pub fn objective(&self) -> impl Fn(&Array1<f64>) -> f64 + '_ {
+        move |x| {
+            self.estimators
+                .iter()
+                .zip(self.mappings.iter())
+                .map(|(e, m)| e.penalized_objective(m(x), x))
+                .sum()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataSet, FailurePenalty, Loss, NelderMead, PredictionOptions, VaporPressure};
+    use feos_core::cubic::{PengRobinson, PengRobinsonParameters};
+    use feos_core::EosError;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    /// Peng-Robinson equation of state with a common acentric factor.
+    fn mapping(tc: f64, pc: f64) -> Box<ParameterMapping<PengRobinson>> {
+        Box::new(move |p| {
+            let parameters = PengRobinsonParameters::new_simple(&[tc], &[pc], &[p[0]], &[1.0])
+                .map_err(feos_core::EosError::from)?;
+            Ok(Rc::new(PengRobinson::new(Rc::new(parameters))))
+        })
+    }
+
+    fn estimator(
+        mapping: &ParameterMapping<PengRobinson>,
+        temperature: &[f64],
+    ) -> Estimator<SIUnit, PengRobinson> {
+        let eos = mapping(&arr1(&[0.15])).unwrap();
+        let temperature = arr1(temperature) * KELVIN;
+        let options = PredictionOptions::default();
+        let data = VaporPressure::new(
+            arr1(&[1e5, 1e5]) * PASCAL,
+            temperature.clone(),
+            Default::default(),
+            options,
+        )
+        .unwrap();
+        let target = data.predict(&eos).unwrap();
+        let data = VaporPressure::new(target, temperature, Default::default(), options).unwrap();
+        Estimator::new(vec![Rc::new(data)], vec![1.0], vec![Loss::Linear])
+    }
+
+    #[test]
+    fn common_parameter() {
+        let (propane, butane) = (mapping(369.96, 42.5e5), mapping(425.1, 38.0e5));
+        let estimators = vec![
+            estimator(&propane, &[250.0, 300.0]),
+            estimator(&butane, &[300.0, 350.0]),
+        ];
+        let multi = MultiEstimator::new(estimators, vec![propane, butane]).unwrap();
+
+        let residuals = multi.residuals(&arr1(&[0.15])).unwrap();
+        assert_eq!(residuals.len(), 4);
+        assert!(residuals.iter().all(|r| r.abs() < 1e-10));
+        let jac = multi.jacobian(&arr1(&[0.15])).unwrap();
+        assert_eq!(jac.dim(), (4, 1));
+        // higher acentric factors lead to lower vapor pressures
+        assert!(jac.iter().all(|&j| j < 0.0));
+
+        let result = NelderMead::default()
+            .minimize(multi.objective(), &arr1(&[0.1]), None)
+            .unwrap();
+        assert!((result.parameters[0] - 0.15).abs() < 1e-4);
+    }
+
+    /// Vapor pressures of butane, the second one is above the critical temperature.
+    fn failing(penalty: Option<FailurePenalty>) -> Estimator<SIUnit, PengRobinson> {
+        let data = VaporPressure::new(
+            arr1(&[1e5, 1e5]) * PASCAL,
+            arr1(&[300.0, 450.0]) * KELVIN,
+            Default::default(),
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        let mut estimator = Estimator::new(vec![Rc::new(data)], vec![1.0], vec![Loss::Linear]);
+        estimator.set_failure_penalty(penalty);
+        estimator
+    }
+
+    #[test]
+    fn failure_penalty() {
+        let x = arr1(&[0.15]);
+        let multi = |penalty: Option<FailurePenalty>| {
+            let (propane, butane) = (mapping(369.96, 42.5e5), mapping(425.1, 38.0e5));
+            let estimators = vec![estimator(&propane, &[250.0, 300.0]), failing(penalty)];
+            MultiEstimator::new(estimators, vec![propane, butane]).unwrap()
+        };
+        let unpenalized = multi(None);
+        assert!(unpenalized.residuals(&x).unwrap()[3].is_nan());
+        assert_eq!(unpenalized.objective()(&x), f64::INFINITY);
+
+        let penalty = FailurePenalty::new(2.0).distance(x.clone(), 10.0);
+        let mut multi = multi(Some(penalty.clone()));
+        let residuals = multi.residuals(&x).unwrap();
+        assert_eq!(residuals[3], 1.0);
+        assert_eq!(multi.objective()(&x), residuals.dot(&residuals));
+        let residuals = multi.residuals(&arr1(&[0.25])).unwrap();
+        assert!((residuals[3] - 1.5).abs() < 1e-12);
+
+        // the equation of state of the third substance can not be created
+        let objective = multi.objective()(&x);
+        multi.add_estimator(
+            failing(Some(penalty)),
+            Box::new(|_| Err(EosError::NotConverged("test".to_owned()).into())),
+        );
+        assert!((multi.objective()(&x) - objective - 2.0).abs() < 1e-12);
+        assert!(multi.residuals(&x).is_err());
+    }
+
+    #[test]
+    fn incompatible_input() {
+        let result = MultiEstimator::<SIUnit, PengRobinson>::new(vec![], vec![mapping(1.0, 1.0)]);
+        assert!(matches!(result, Err(EstimatorError::IncompatibleInput)));
+    }
+}
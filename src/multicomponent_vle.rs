@@ -0,0 +1,338 @@
+use super::{DataSet, EstimatorError, Loss, Prediction, PredictionOptions};
+use crate::dataset::{join, select};
+use feos_core::{Contributions, EosError, EosUnit, EquationOfState, PhaseEquilibrium};
+use ndarray::{concatenate, Array1, Array2, Axis};
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Store experimental VLE data of mixtures with an arbitrary number of components.
+///
+/// For isothermal data, the target is the bubble point pressure,
+/// for isobaric data it is the bubble point temperature.
+#[derive(Clone)]
+pub struct MulticomponentVLE<U: EosUnit> {
+    /// bubble point pressure or temperature
+    pub target: QuantityArray1<U>,
+    /// specified temperature or pressure
+    temperature_or_pressure: QuantityArray1<U>,
+    /// liquid mole fractions of all components
+    liquid_molefracs: Array2<f64>,
+    /// vapor mole fractions of all components
+    vapor_molefracs: Option<Array2<f64>>,
+    /// `true` if the temperature is specified
+    isothermal: bool,
+    /// number of data points
+    datapoints: usize,
+    /// solver options
+    options: PredictionOptions,
+}
+
+impl<U: EosUnit> MulticomponentVLE<U> {
+    /// Create a new data set for VLE data of a multicomponent mixture.
+    ///
+    /// If `temperature_or_pressure` is a temperature, the target has to be
+    /// the bubble point pressure and vice versa.
+    /// The mole fractions are stored with one row per data point and
+    /// one column per component. If `vapor_molefracs` are given, the
+    /// deviations of the vapor compositions are part of the cost function.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature_or_pressure: QuantityArray1<U>,
+        liquid_molefracs: Array2<f64>,
+        vapor_molefracs: Option<Array2<f64>>,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        let vapor_dim = match &vapor_molefracs {
+            Some(y) => y.dim(),
+            None => liquid_molefracs.dim(),
+        };
+        if temperature_or_pressure.len() != datapoints
+            || liquid_molefracs.nrows() != datapoints
+            || vapor_dim != liquid_molefracs.dim()
+        {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let isothermal = if temperature_or_pressure.has_unit(&U::reference_temperature()) {
+            true
+        } else if temperature_or_pressure.has_unit(&U::reference_pressure()) {
+            false
+        } else {
+            return Err(EstimatorError::NoTemperatureOrPressure);
+        };
+        Ok(Self {
+            target,
+            temperature_or_pressure,
+            liquid_molefracs,
+            vapor_molefracs,
+            isothermal,
+            datapoints,
+            options,
+        })
+    }
+
+    /// Returns the specified temperature or pressure of the data points.
+    pub fn temperature_or_pressure(&self) -> QuantityArray1<U> {
+        self.temperature_or_pressure.clone()
+    }
+
+    /// Returns the liquid mole fractions of the data points.
+    pub fn liquid_molefracs(&self) -> Array2<f64> {
+        self.liquid_molefracs.clone()
+    }
+
+    /// Returns the vapor mole fractions of the data points.
+    pub fn vapor_molefracs(&self) -> Option<Array2<f64>> {
+        self.vapor_molefracs.clone()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        if self.liquid_molefracs.ncols() != other.liquid_molefracs.ncols() {
+            return Err(EstimatorError::IncompatibleDataSets);
+        }
+        let vapor_molefracs = match (&self.vapor_molefracs, &other.vapor_molefracs) {
+            (Some(y1), Some(y2)) => Some(concatenate(Axis(0), &[y1.view(), y2.view()])?),
+            (None, None) => None,
+            _ => return Err(EstimatorError::IncompatibleDataSets),
+        };
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(
+                &self.temperature_or_pressure,
+                &other.temperature_or_pressure,
+            )?,
+            concatenate(
+                Axis(0),
+                &[self.liquid_molefracs.view(), other.liquid_molefracs.view()],
+            )?,
+            vapor_molefracs,
+            self.options,
+        )
+    }
+
+    /// Description of the specified variable.
+    fn specification_str(&self) -> &str {
+        if self.isothermal {
+            "temperature"
+        } else {
+            "pressure"
+        }
+    }
+
+    /// Returns the bubble point pressures or temperatures and the vapor
+    /// compositions of the model. Failed calculations are set to `NAN`.
+    pub fn predict_bubble_points<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<(QuantityArray1<U>, Array2<f64>), EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let (prediction, vapor_molefracs) = self.predict_bubble_points_with_status(eos)?;
+        Ok((prediction.values, vapor_molefracs))
+    }
+
+    /// Bubble point pressures or temperatures together with the status
+    /// of every data point, and the vapor compositions of the model.
+    fn predict_bubble_points_with_status<E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<(Prediction<U>, Array2<f64>), EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display,
+    {
+        let components = self.liquid_molefracs.ncols();
+        if eos.components() != components {
+            return Err(EstimatorError::EosError(EosError::IncompatibleComponents(
+                eos.components(),
+                components,
+            )));
+        }
+        let mut vapor_molefracs = Array2::from_elem(self.liquid_molefracs.dim(), f64::NAN);
+        let mut results = Vec::with_capacity(self.datapoints);
+        for i in 0..self.datapoints {
+            let y_init = self.vapor_molefracs.as_ref().map(|y| y.row(i).to_owned());
+            let vle = PhaseEquilibrium::bubble_point(
+                eos,
+                self.temperature_or_pressure.get(i),
+                &self.liquid_molefracs.row(i).to_owned(),
+                Some(self.target.get(i)),
+                y_init.as_ref(),
+                self.options.bubble_dew(),
+            );
+            results.push(vle.map(|vle| {
+                vapor_molefracs.row_mut(i).assign(&vle.vapor().molefracs);
+                if self.isothermal {
+                    vle.vapor().pressure(Contributions::Total)
+                } else {
+                    vle.vapor().temperature
+                }
+            }));
+        }
+        let prediction = Prediction::from_results(results, self.target.get(0))?;
+        Ok((prediction, vapor_molefracs))
+    }
+}
+
+impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for MulticomponentVLE<U> {
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        if self.isothermal {
+            "bubble point pressure"
+        } else {
+            "bubble point temperature"
+        }
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        let mut input = vec![self.specification_str(), "liquid molefracs"];
+        if self.vapor_molefracs.is_some() {
+            input.push("vapor molefracs");
+        }
+        input
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_bubble_points(eos)?.0)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_bubble_points_with_status(eos)?.0)
+    }
+
+    /// The cost contains the relative differences of the bubble point
+    /// pressures or temperatures. If vapor compositions are available,
+    /// the differences of the vapor mole fractions of all components
+    /// are appended for each data point.
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let (prediction, vapor_molefracs) = self.predict_bubble_points(eos)?;
+        let cost = ((prediction - &self.target) / &self.target).into_value()?;
+        let mut cost = match &self.vapor_molefracs {
+            Some(y) => {
+                let dy = Array1::from_iter(vapor_molefracs - y);
+                concatenate![Axis(0), cost, dy]
+            }
+            None => cost,
+        };
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn n_residuals(&self) -> usize {
+        match &self.vapor_molefracs {
+            Some(y) => self.datapoints + y.len(),
+            None => self.datapoints,
+        }
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature_or_pressure, indices)?,
+            self.liquid_molefracs.select(Axis(0), indices),
+            self.vapor_molefracs
+                .as_ref()
+                .map(|y| y.select(Axis(0), indices)),
+            self.options,
+        )?))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let components = self.liquid_molefracs.ncols();
+        let mut m = HashMap::with_capacity(1 + 2 * components);
+        m.insert(
+            self.specification_str().to_owned(),
+            self.temperature_or_pressure(),
+        );
+        for (i, x) in self.liquid_molefracs.columns().into_iter().enumerate() {
+            m.insert(format!("liquid molefracs {}", i), x.to_owned().into());
+        }
+        if let Some(y) = &self.vapor_molefracs {
+            for (i, y) in y.columns().into_iter().enumerate() {
+                m.insert(format!("vapor molefracs {}", i), y.to_owned().into());
+            }
+        }
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feos_core::cubic::{PengRobinson, PengRobinsonParameters};
+    use ndarray::{arr1, arr2};
+    use quantity::si::*;
+
+    fn alkanes(n: usize) -> Rc<PengRobinson> {
+        let parameters = PengRobinsonParameters::new_simple(
+            &[369.96, 425.1, 469.7][..n],
+            &[42.5e5, 38.0e5, 33.7e5][..n],
+            &[0.153, 0.2, 0.252][..n],
+            &[44.0962, 58.123, 72.15][..n],
+        )
+        .unwrap();
+        Rc::new(PengRobinson::new(Rc::new(parameters)))
+    }
+
+    #[test]
+    fn ternary_bubble_points() {
+        let eos = alkanes(3);
+        let temperature = arr1(&[280.0, 300.0]) * KELVIN;
+        let x = arr2(&[[0.2, 0.3, 0.5], [0.5, 0.3, 0.2]]);
+        let options = PredictionOptions::default();
+        let data = MulticomponentVLE::new(
+            arr1(&[1e5, 1e5]) * PASCAL,
+            temperature.clone(),
+            x.clone(),
+            None,
+            options,
+        )
+        .unwrap();
+        let (pressure, y) = data.predict_bubble_points(&eos).unwrap();
+        for row in y.rows() {
+            assert!((row.sum() - 1.0).abs() < 1e-10);
+        }
+
+        let data = MulticomponentVLE::new(pressure, temperature, x, Some(y), options).unwrap();
+        let cost = data.cost(&eos, Loss::Linear).unwrap();
+        assert_eq!(cost.len(), 8);
+        assert_eq!(DataSet::<SIUnit, PengRobinson>::n_residuals(&data), 8);
+        assert!(cost.iter().all(|c| c.abs() < 1e-6));
+    }
+
+    #[test]
+    fn incompatible_components() {
+        let data = MulticomponentVLE::new(
+            arr1(&[1e5]) * PASCAL,
+            arr1(&[300.0]) * KELVIN,
+            arr2(&[[0.2, 0.3, 0.5]]),
+            None,
+            PredictionOptions::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            data.predict(&alkanes(2)),
+            Err(EstimatorError::EosError(EosError::IncompatibleComponents(
+                2, 3
+            )))
+        ));
+    }
+}
@@ -0,0 +1,231 @@
+use super::{distinct_minima, latin_hypercube, Bounds, Objective, OptimizationResult};
+use crate::EstimatorError;
+use ndarray::Array1;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Differential evolution (DE/rand/1/bin).
+///
+/// The initial population is a Latin hypercube sample within the bounds.
+/// Mutants are projected onto the bounds. The results are deterministic
+/// for a given seed.
+#[derive(Clone, Copy, Debug)]
+pub struct DifferentialEvolution {
+    /// number of individuals per parameter
+    pub population_factor: usize,
+    /// maximum number of generations
+    pub max_iter: usize,
+    /// differential weight
+    pub mutation: f64,
+    /// crossover probability
+    pub crossover: f64,
+    /// tolerance for the standard deviation of the objective function in
+    /// the population relative to its mean
+    pub tol: f64,
+    /// minimum distance between distinct minima relative to the width of the bounds
+    pub distance: f64,
+    /// seed of the random number generator
+    pub seed: u64,
+}
+
+impl Default for DifferentialEvolution {
+    fn default() -> Self {
+        Self {
+            population_factor: 15,
+            max_iter: 1000,
+            mutation: 0.8,
+            crossover: 0.9,
+            tol: 1e-6,
+            distance: 1e-3,
+            seed: 0,
+        }
+    }
+}
+
+impl DifferentialEvolution {
+    /// Set the number of individuals per parameter.
+    pub fn population_factor(mut self, population_factor: usize) -> Self {
+        self.population_factor = population_factor;
+        self
+    }
+
+    /// Set the maximum number of generations.
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Set the differential weight.
+    pub fn mutation(mut self, mutation: f64) -> Self {
+        self.mutation = mutation;
+        self
+    }
+
+    /// Set the crossover probability.
+    pub fn crossover(mut self, crossover: f64) -> Self {
+        self.crossover = crossover;
+        self
+    }
+
+    /// Set the relative tolerance of the objective function.
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Set the minimum distance between distinct minima.
+    pub fn distance(mut self, distance: f64) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    /// Set the seed of the random number generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Minimize `f` within `bounds`.
+    ///
+    /// Returns up to `minima` distinct individuals of the final population
+    /// sorted by their objective function. All results share the number of
+    /// iterations, evaluations and the history of the best individual.
+    pub fn minimize<F: FnMut(&Array1<f64>) -> f64>(
+        &self,
+        f: F,
+        bounds: &Bounds,
+        minima: usize,
+    ) -> Result<Vec<OptimizationResult>, EstimatorError> {
+        if bounds.is_empty() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let mut objective = Objective::new(f);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let n = bounds.len();
+        let size = (self.population_factor * n).max(5);
+
+        let mut population = latin_hypercube(size, bounds, &mut rng);
+        let mut energies: Vec<f64> = population.iter().map(|x| objective.evaluate(x)).collect();
+
+        let mut history = Vec::new();
+        let mut converged = false;
+        let mut iterations = 0;
+        while iterations < self.max_iter {
+            let best = (0..size)
+                .min_by(|&a, &b| energies[a].total_cmp(&energies[b]))
+                .unwrap();
+            history.push((population[best].clone(), energies[best]));
+            if self.converged(&energies) {
+                converged = true;
+                break;
+            }
+            iterations += 1;
+
+            for i in 0..size {
+                let [a, b, c] = distinct_indices(i, size, &mut rng);
+                let mutant = bounds
+                    .clip(&(&population[a] + &((&population[b] - &population[c]) * self.mutation)));
+                let j_rand = rng.gen_range(0..n);
+                let mut trial = population[i].clone();
+                for j in 0..n {
+                    if j == j_rand || rng.gen::<f64>() < self.crossover {
+                        trial[j] = mutant[j];
+                    }
+                }
+                let f_trial = objective.evaluate(&trial);
+                if f_trial <= energies[i] {
+                    population[i] = trial;
+                    energies[i] = f_trial;
+                }
+            }
+        }
+
+        let results = population
+            .into_iter()
+            .zip(energies)
+            .map(|(parameters, cost)| OptimizationResult {
+                parameters,
+                cost,
+                iterations,
+                evaluations: objective.evaluations,
+                converged,
+                history: history.clone(),
+            })
+            .collect();
+        Ok(distinct_minima(results, bounds, self.distance, minima))
+    }
+
+    /// Check the spread of the objective function in the population.
+    fn converged(&self, energies: &[f64]) -> bool {
+        if energies.iter().any(|e| !e.is_finite()) {
+            return false;
+        }
+        let n = energies.len() as f64;
+        let mean = energies.iter().sum::<f64>() / n;
+        let var = energies.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / n;
+        var.sqrt() <= self.tol * mean.abs()
+    }
+}
+
+/// Three mutually different indices that are different from `i`.
+fn distinct_indices<R: Rng>(i: usize, size: usize, rng: &mut R) -> [usize; 3] {
+    let mut indices = [i; 3];
+    for k in 0..3 {
+        loop {
+            let r = rng.gen_range(0..size);
+            if r != i && !indices[..k].contains(&r) {
+                indices[k] = r;
+                break;
+            }
+        }
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    /// Double well with the global minimum close to `x = -1`.
+    fn double_well(x: &Array1<f64>) -> f64 {
+        (x[0].powi(2) - 1.0).powi(2) + 0.1 * x[0] + x[1].powi(2) + 1.0
+    }
+
+    #[test]
+    fn global_minimum() {
+        let bounds = Bounds::new(arr1(&[-2.0, -2.0]), arr1(&[2.0, 2.0])).unwrap();
+        let results = DifferentialEvolution::default()
+            .minimize(double_well, &bounds, 1)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert!(result.converged);
+        assert!((result.parameters[0] + 1.0).abs() < 0.05);
+        assert!(result.parameters[1].abs() < 0.05);
+        assert_eq!(result.history.len(), result.iterations + 1);
+    }
+
+    #[test]
+    fn deterministic() {
+        let bounds = Bounds::new(arr1(&[-2.0, -2.0]), arr1(&[2.0, 2.0])).unwrap();
+        let de = DifferentialEvolution::default().max_iter(20);
+        let a = de.minimize(double_well, &bounds, 3).unwrap();
+        let b = de.minimize(double_well, &bounds, 3).unwrap();
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert_eq!(a.parameters, b.parameters);
+        }
+        let c = de.seed(1).minimize(double_well, &bounds, 1).unwrap();
+        assert_ne!(a[0].parameters, c[0].parameters);
+    }
+
+    #[test]
+    fn distinct_indices_differ() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for i in 0..5 {
+            let [a, b, c] = distinct_indices(i, 5, &mut rng);
+            assert!(a != i && b != i && c != i);
+            assert!(a != b && b != c && a != c);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+//! Optimizers that minimize the sum of squares of the cost of an [Estimator](crate::Estimator).
+//!
+//! The optimizers operate on an objective function that maps a vector
+//! of parameters to a scalar, e.g. the one returned by
+//! [Estimator::objective](crate::Estimator::objective).
+//! Failed evaluations (`NAN`) are treated as infinitely large.
+use crate::linalg::inverse;
+use crate::EstimatorError;
+use ndarray::{Array1, Array2, Zip};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+mod differential_evolution;
+mod multistart;
+mod nelder_mead;
+mod pattern_search;
+mod profile_likelihood;
+mod transformation;
+pub use differential_evolution::DifferentialEvolution;
+pub use multistart::Multistart;
+pub use nelder_mead::NelderMead;
+pub use pattern_search::PatternSearch;
+pub use profile_likelihood::{Profile, ProfileLikelihood};
+pub use transformation::{ParameterTransformation, Transformation};
+
+/// Relative step size of the finite differences.
+const FD_STEP: f64 = 1e-6;
+
+/// Lower and upper bounds of the parameters.
+#[derive(Clone, Debug)]
+pub struct Bounds {
+    pub lower: Array1<f64>,
+    pub upper: Array1<f64>,
+}
+
+impl Bounds {
+    /// Create new bounds. Every lower bound has to be smaller
+    /// than or equal to the corresponding upper bound.
+    pub fn new(lower: Array1<f64>, upper: Array1<f64>) -> Result<Self, EstimatorError> {
+        if lower.len() != upper.len() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        if lower
+            .iter()
+            .zip(upper.iter())
+            .any(|(l, u)| l > u || l.is_nan() || u.is_nan())
+        {
+            return Err(EstimatorError::InvalidBounds);
+        }
+        Ok(Self { lower, upper })
+    }
+
+    /// Returns the number of parameters.
+    pub fn len(&self) -> usize {
+        self.lower.len()
+    }
+
+    /// Returns `true` if there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.lower.is_empty()
+    }
+
+    /// Returns the point within the bounds that is closest to `x`.
+    pub fn clip(&self, x: &Array1<f64>) -> Array1<f64> {
+        Zip::from(x)
+            .and(&self.lower)
+            .and(&self.upper)
+            .map_collect(|&x, &l, &u| x.max(l).min(u))
+    }
+}
+
+/// Result of an optimization.
+#[derive(Clone, Debug)]
+pub struct OptimizationResult {
+    /// optimal parameters
+    pub parameters: Array1<f64>,
+    /// objective function at the optimal parameters
+    pub cost: f64,
+    /// number of iterations
+    pub iterations: usize,
+    /// number of evaluations of the objective function
+    pub evaluations: usize,
+    /// `true` if the convergence criterion was met
+    pub converged: bool,
+    /// best parameters and objective function after every iteration
+    pub history: Vec<(Array1<f64>, f64)>,
+}
+
+/// Jacobian of the residuals `f` with respect to the parameters
+/// evaluated at `x` using central finite differences.
+pub fn jacobian<F>(mut f: F, x: &Array1<f64>) -> Result<Array2<f64>, EstimatorError>
+where
+    F: FnMut(&Array1<f64>) -> Result<Array1<f64>, EstimatorError>,
+{
+    let mut columns = Vec::with_capacity(x.len());
+    for i in 0..x.len() {
+        let h = FD_STEP * x[i].abs().max(1.0);
+        let mut x_p = x.clone();
+        let mut x_m = x.clone();
+        x_p[i] += h;
+        x_m[i] -= h;
+        columns.push((f(&x_p)? - f(&x_m)?) / (2.0 * h));
+    }
+    let m = match columns.first() {
+        Some(c) => c.len(),
+        None => return Err(EstimatorError::IncompatibleInput),
+    };
+    let mut jac = Array2::zeros((m, x.len()));
+    for (mut c, column) in jac.columns_mut().into_iter().zip(columns.iter()) {
+        c.assign(column);
+    }
+    Ok(jac)
+}
+
+/// Covariance matrix of the parameters in a least squares problem.
+///
+/// The covariance is approximated as `s^2 (J^T J)^-1` where `J` is the Jacobian
+/// of the residuals and `s^2` is the sum of squared residuals divided by the
+/// degrees of freedom.
+pub fn covariance(
+    jacobian: &Array2<f64>,
+    residuals: &Array1<f64>,
+) -> Result<Array2<f64>, EstimatorError> {
+    let (m, n) = jacobian.dim();
+    if residuals.len() != m || m <= n {
+        return Err(EstimatorError::IncompatibleInput);
+    }
+    let s2 = residuals.dot(residuals) / (m - n) as f64;
+    Ok(inverse(&jacobian.t().dot(jacobian))? * s2)
+}
+
+/// Sum of squares of the cost. Failed evaluations result in infinity.
+pub(crate) fn sum_of_squares(cost: Result<Array1<f64>, EstimatorError>) -> f64 {
+    match cost {
+        Ok(cost) => {
+            let f = cost.dot(&cost);
+            if f.is_nan() {
+                f64::INFINITY
+            } else {
+                f
+            }
+        }
+        Err(_) => f64::INFINITY,
+    }
+}
+
+/// Objective function that counts evaluations and maps `NAN` to infinity.
+pub(crate) struct Objective<F> {
+    f: F,
+    evaluations: usize,
+}
+
+impl<F: FnMut(&Array1<f64>) -> f64> Objective<F> {
+    pub(crate) fn new(f: F) -> Self {
+        Self { f, evaluations: 0 }
+    }
+
+    pub(crate) fn evaluate(&mut self, x: &Array1<f64>) -> f64 {
+        self.evaluations += 1;
+        let f = (self.f)(x);
+        if f.is_nan() {
+            f64::INFINITY
+        } else {
+            f
+        }
+    }
+}
+
+/// Check the dimensions of the initial parameters and bounds and
+/// move the initial parameters into the bounds.
+pub(crate) fn initial_parameters(
+    x0: &Array1<f64>,
+    bounds: Option<&Bounds>,
+) -> Result<Array1<f64>, EstimatorError> {
+    if x0.is_empty() {
+        return Err(EstimatorError::IncompatibleInput);
+    }
+    match bounds {
+        Some(b) if b.len() != x0.len() => Err(EstimatorError::IncompatibleInput),
+        Some(b) => Ok(b.clip(x0)),
+        None => Ok(x0.clone()),
+    }
+}
+
+/// Initial step size of parameter `x`.
+pub(crate) fn initial_step(x: f64, relative_step: f64) -> f64 {
+    if x == 0.0 {
+        relative_step
+    } else {
+        relative_step * x.abs()
+    }
+}
+
+/// Latin hypercube sample of `n` points within the bounds.
+pub(crate) fn latin_hypercube<R: Rng>(n: usize, bounds: &Bounds, rng: &mut R) -> Vec<Array1<f64>> {
+    let mut points = vec![Array1::zeros(bounds.len()); n];
+    let mut strata: Vec<usize> = (0..n).collect();
+    for j in 0..bounds.len() {
+        strata.shuffle(rng);
+        let (l, u) = (bounds.lower[j], bounds.upper[j]);
+        for (x, &k) in points.iter_mut().zip(strata.iter()) {
+            x[j] = l + (k as f64 + rng.gen::<f64>()) / n as f64 * (u - l);
+        }
+    }
+    points
+}
+
+/// Select the best `n` results whose parameters differ by more than
+/// `distance` relative to the width of the bounds.
+pub(crate) fn distinct_minima(
+    mut results: Vec<OptimizationResult>,
+    bounds: &Bounds,
+    distance: f64,
+    n: usize,
+) -> Vec<OptimizationResult> {
+    let width = &bounds.upper - &bounds.lower;
+    results.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+    let mut minima: Vec<OptimizationResult> = Vec::with_capacity(n);
+    for result in results {
+        if minima.len() == n {
+            break;
+        }
+        let distinct = minima.iter().all(|m| {
+            Zip::from(&m.parameters)
+                .and(&result.parameters)
+                .and(&width)
+                .fold(0.0, |acc: f64, x, y, w| {
+                    if *w > 0.0 {
+                        acc.max((x - y).abs() / w)
+                    } else {
+                        acc
+                    }
+                })
+                > distance
+        });
+        if distinct {
+            minima.push(result);
+        }
+    }
+    minima
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{arr1, arr2, s};
+
+    #[test]
+    fn bounds() {
+        assert!(matches!(
+            Bounds::new(arr1(&[0.0, 1.0]), arr1(&[1.0])),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+        assert!(matches!(
+            Bounds::new(arr1(&[0.0, 2.0]), arr1(&[1.0, 1.0])),
+            Err(EstimatorError::InvalidBounds)
+        ));
+        assert!(matches!(
+            Bounds::new(arr1(&[f64::NAN]), arr1(&[1.0])),
+            Err(EstimatorError::InvalidBounds)
+        ));
+        let bounds = Bounds::new(arr1(&[0.0, 0.0]), arr1(&[1.0, 2.0])).unwrap();
+        assert_eq!(bounds.clip(&arr1(&[-1.0, 3.0])), arr1(&[0.0, 2.0]));
+        assert_eq!(bounds.clip(&arr1(&[0.5, 1.5])), arr1(&[0.5, 1.5]));
+    }
+
+    #[test]
+    fn initial_parameters_are_clipped() {
+        let bounds = Bounds::new(arr1(&[0.0, 0.0]), arr1(&[1.0, 1.0])).unwrap();
+        let x0 = initial_parameters(&arr1(&[2.0, 0.5]), Some(&bounds)).unwrap();
+        assert_eq!(x0, arr1(&[1.0, 0.5]));
+        assert!(initial_parameters(&arr1(&[0.5]), Some(&bounds)).is_err());
+        assert!(initial_parameters(&arr1(&[]), None).is_err());
+    }
+
+    #[test]
+    fn failed_evaluations() {
+        assert_eq!(sum_of_squares(Ok(arr1(&[1.0, 2.0]))), 5.0);
+        assert_eq!(sum_of_squares(Ok(arr1(&[1.0, f64::NAN]))), f64::INFINITY);
+        assert_eq!(
+            sum_of_squares(Err(EstimatorError::IncompatibleInput)),
+            f64::INFINITY
+        );
+        let mut objective = Objective::new(|x: &Array1<f64>| x[0].sqrt());
+        assert_eq!(objective.evaluate(&arr1(&[-1.0])), f64::INFINITY);
+        assert_eq!(objective.evaluate(&arr1(&[4.0])), 2.0);
+        assert_eq!(objective.evaluations, 2);
+    }
+
+    #[test]
+    fn latin_hypercube_strata() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let bounds = Bounds::new(arr1(&[0.0, -1.0]), arr1(&[1.0, 1.0])).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = latin_hypercube(10, &bounds, &mut rng);
+        for j in 0..2 {
+            let (l, u) = (bounds.lower[j], bounds.upper[j]);
+            let mut strata: Vec<_> = points
+                .iter()
+                .map(|x| ((x[j] - l) / (u - l) * 10.0).floor() as usize)
+                .collect();
+            strata.sort_unstable();
+            assert_eq!(strata, (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn distinct() {
+        let bounds = Bounds::new(arr1(&[0.0]), arr1(&[10.0])).unwrap();
+        let result = |x: f64, cost: f64| OptimizationResult {
+            parameters: arr1(&[x]),
+            cost,
+            iterations: 0,
+            evaluations: 0,
+            converged: true,
+            history: Vec::new(),
+        };
+        let results = vec![
+            result(5.0, 2.0),
+            result(1.0, 0.0),
+            result(1.001, 1.0),
+            result(8.0, 3.0),
+        ];
+        let minima = distinct_minima(results, &bounds, 1e-3, 2);
+        let x: Vec<_> = minima.iter().map(|m| m.parameters[0]).collect();
+        assert_eq!(x, vec![1.0, 5.0]);
+    }
+
+    #[test]
+    fn jacobian_of_linear_residuals() {
+        let a = arr2(&[[1.0, 2.0], [3.0, 4.0], [5.0, -6.0]]);
+        let residuals = |x: &Array1<f64>| Ok(a.dot(x));
+        let jac = jacobian(residuals, &arr1(&[0.5, 1e3])).unwrap();
+        for (j, a) in jac.iter().zip(a.iter()) {
+            assert!((j - a).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn covariance_of_line_fit() {
+        // y = c_0 + c_1 x at x = 0, 1, 2, 3
+        let jac = arr2(&[[1.0, 0.0], [1.0, 1.0], [1.0, 2.0], [1.0, 3.0]]);
+        let residuals = arr1(&[0.1, -0.1, 0.1, -0.1]);
+        let cov = covariance(&jac, &residuals).unwrap();
+        // s^2 = 0.04 / 2 and (J^T J)^-1 = [[0.7, -0.3], [-0.3, 0.2]]
+        let expected = arr2(&[[0.7, -0.3], [-0.3, 0.2]]) * 0.02;
+        for (c, e) in cov.iter().zip(expected.iter()) {
+            assert!((c - e).abs() < 1e-14);
+        }
+        assert!(matches!(
+            covariance(&jac.slice(s![..2, ..]).to_owned(), &arr1(&[0.1, 0.1])),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+    }
+}
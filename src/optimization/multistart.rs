@@ -0,0 +1,136 @@
+use super::{distinct_minima, latin_hypercube, Bounds, NelderMead, Objective, OptimizationResult};
+use crate::EstimatorError;
+use ndarray::Array1;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Multistart optimization with local refinement.
+///
+/// The objective function is evaluated on a Latin hypercube sample within
+/// the bounds. The best samples are used as initial values for local
+/// optimizations with the Nelder-Mead algorithm. The results are
+/// deterministic for a given seed.
+#[derive(Clone, Copy, Debug)]
+pub struct Multistart {
+    /// number of samples of the Latin hypercube
+    pub samples: usize,
+    /// number of samples that are refined locally
+    pub starts: usize,
+    /// local optimizer
+    pub local: NelderMead,
+    /// minimum distance between distinct minima relative to the width of the bounds
+    pub distance: f64,
+    /// seed of the random number generator
+    pub seed: u64,
+}
+
+impl Default for Multistart {
+    fn default() -> Self {
+        Self {
+            samples: 100,
+            starts: 10,
+            local: NelderMead::default(),
+            distance: 1e-3,
+            seed: 0,
+        }
+    }
+}
+
+impl Multistart {
+    /// Set the number of samples of the Latin hypercube.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Set the number of samples that are refined locally.
+    pub fn starts(mut self, starts: usize) -> Self {
+        self.starts = starts;
+        self
+    }
+
+    /// Set the local optimizer.
+    pub fn local(mut self, local: NelderMead) -> Self {
+        self.local = local;
+        self
+    }
+
+    /// Set the minimum distance between distinct minima.
+    pub fn distance(mut self, distance: f64) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    /// Set the seed of the random number generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Minimize `f` within `bounds`.
+    ///
+    /// Returns up to `minima` distinct results of the local
+    /// optimizations sorted by their objective function.
+    pub fn minimize<F: FnMut(&Array1<f64>) -> f64>(
+        &self,
+        f: F,
+        bounds: &Bounds,
+        minima: usize,
+    ) -> Result<Vec<OptimizationResult>, EstimatorError> {
+        if bounds.is_empty() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let mut objective = Objective::new(f);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut samples: Vec<_> = latin_hypercube(self.samples, bounds, &mut rng)
+            .into_iter()
+            .map(|x| {
+                let fx = objective.evaluate(&x);
+                (x, fx)
+            })
+            .collect();
+        samples.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let results = samples
+            .iter()
+            .take(self.starts)
+            .filter(|(_, fx)| fx.is_finite())
+            .map(|(x, _)| {
+                self.local
+                    .minimize(|x: &Array1<f64>| objective.evaluate(x), x, Some(bounds))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(distinct_minima(results, bounds, self.distance, minima))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    #[test]
+    fn both_minima() {
+        let f = |x: &Array1<f64>| (x[0].powi(2) - 1.0).powi(2) + 0.1 * x[0];
+        let bounds = Bounds::new(arr1(&[-2.0]), arr1(&[2.0])).unwrap();
+        let results = Multistart::default()
+            .samples(20)
+            .starts(20)
+            .distance(0.1)
+            .minimize(f, &bounds, 2)
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].cost < results[1].cost);
+        assert!((results[0].parameters[0] + 1.0).abs() < 0.05);
+        assert!((results[1].parameters[0] - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn no_finite_samples() {
+        let bounds = Bounds::new(arr1(&[-2.0]), arr1(&[2.0])).unwrap();
+        let results = Multistart::default()
+            .minimize(|_: &Array1<f64>| f64::NAN, &bounds, 1)
+            .unwrap();
+        assert!(results.is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+use super::{initial_parameters, initial_step, Bounds, Objective, OptimizationResult};
+use crate::EstimatorError;
+use ndarray::Array1;
+
+const REFLECTION: f64 = 1.0;
+const EXPANSION: f64 = 2.0;
+const CONTRACTION: f64 = 0.5;
+const SHRINKAGE: f64 = 0.5;
+
+/// Nelder-Mead simplex algorithm.
+///
+/// Bounds are handled by projecting all trial points onto the bounds.
+#[derive(Clone, Copy, Debug)]
+pub struct NelderMead {
+    /// maximum number of iterations
+    pub max_iter: usize,
+    /// tolerance for the spread of the objective function in the simplex
+    pub tol_f: f64,
+    /// tolerance for the size of the simplex
+    pub tol_x: f64,
+    /// size of the initial simplex relative to the initial parameters
+    pub initial_step: f64,
+}
+
+impl Default for NelderMead {
+    fn default() -> Self {
+        Self {
+            max_iter: 1000,
+            tol_f: 1e-10,
+            tol_x: 1e-8,
+            initial_step: 0.05,
+        }
+    }
+}
+
+impl NelderMead {
+    /// Set the maximum number of iterations.
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Set the tolerance for the objective function.
+    pub fn tol_f(mut self, tol_f: f64) -> Self {
+        self.tol_f = tol_f;
+        self
+    }
+
+    /// Set the tolerance for the parameters.
+    pub fn tol_x(mut self, tol_x: f64) -> Self {
+        self.tol_x = tol_x;
+        self
+    }
+
+    /// Set the relative size of the initial simplex.
+    pub fn initial_step(mut self, initial_step: f64) -> Self {
+        self.initial_step = initial_step;
+        self
+    }
+
+    /// Minimize `f` starting from `x0`.
+    pub fn minimize<F: FnMut(&Array1<f64>) -> f64>(
+        &self,
+        f: F,
+        x0: &Array1<f64>,
+        bounds: Option<&Bounds>,
+    ) -> Result<OptimizationResult, EstimatorError> {
+        let mut objective = Objective::new(f);
+        let project = |x: Array1<f64>| match bounds {
+            Some(b) => b.clip(&x),
+            None => x,
+        };
+        let x0 = initial_parameters(x0, bounds)?;
+        let n = x0.len();
+
+        // initial simplex
+        let mut simplex = Vec::with_capacity(n + 1);
+        simplex.push((x0.clone(), objective.evaluate(&x0)));
+        for i in 0..n {
+            let mut x = x0.clone();
+            let step = initial_step(x0[i], self.initial_step);
+            x[i] += step;
+            if let Some(b) = bounds {
+                if x[i] > b.upper[i] {
+                    x[i] = x0[i] - step;
+                }
+            }
+            let x = project(x);
+            let fx = objective.evaluate(&x);
+            simplex.push((x, fx));
+        }
+
+        let mut history = Vec::new();
+        let mut converged = false;
+        let mut iterations = 0;
+        while iterations < self.max_iter {
+            simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+            history.push(simplex[0].clone());
+            if self.converged(&simplex) {
+                converged = true;
+                break;
+            }
+            iterations += 1;
+
+            let centroid = simplex[..n]
+                .iter()
+                .fold(Array1::zeros(n), |acc, (x, _)| acc + x)
+                / n as f64;
+            let (worst, f_worst) = simplex[n].clone();
+            let f_best = simplex[0].1;
+            let f_second_worst = simplex[n - 1].1;
+
+            let xr = project(&centroid + &((&centroid - &worst) * REFLECTION));
+            let fr = objective.evaluate(&xr);
+            if fr < f_best {
+                let xe = project(&centroid + &((&xr - &centroid) * EXPANSION));
+                let fe = objective.evaluate(&xe);
+                simplex[n] = if fe < fr { (xe, fe) } else { (xr, fr) };
+                continue;
+            }
+            if fr < f_second_worst {
+                simplex[n] = (xr, fr);
+                continue;
+            }
+            if fr < f_worst {
+                let xc = project(&centroid + &((&xr - &centroid) * CONTRACTION));
+                let fc = objective.evaluate(&xc);
+                if fc <= fr {
+                    simplex[n] = (xc, fc);
+                    continue;
+                }
+            } else {
+                let xc = project(&centroid + &((&worst - &centroid) * CONTRACTION));
+                let fc = objective.evaluate(&xc);
+                if fc < f_worst {
+                    simplex[n] = (xc, fc);
+                    continue;
+                }
+            }
+
+            // shrink towards the best vertex
+            let best = simplex[0].0.clone();
+            for (x, fx) in simplex.iter_mut().skip(1) {
+                *x = project(&best + &((&*x - &best) * SHRINKAGE));
+                *fx = objective.evaluate(x);
+            }
+        }
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (parameters, cost) = simplex.swap_remove(0);
+        Ok(OptimizationResult {
+            parameters,
+            cost,
+            iterations,
+            evaluations: objective.evaluations,
+            converged,
+            history,
+        })
+    }
+
+    /// Check the spread of the objective function and the size of the simplex.
+    fn converged(&self, simplex: &[(Array1<f64>, f64)]) -> bool {
+        let (best, f_best) = &simplex[0];
+        if !f_best.is_finite() {
+            return false;
+        }
+        let f_spread = simplex
+            .iter()
+            .map(|(_, f)| (f - f_best).abs())
+            .fold(0.0, f64::max);
+        let x_spread = simplex
+            .iter()
+            .flat_map(|(x, _)| {
+                x.iter()
+                    .zip(best.iter())
+                    .map(|(xi, bi)| (xi - bi).abs() / bi.abs().max(1.0))
+            })
+            .fold(0.0, f64::max);
+        f_spread <= self.tol_f && x_spread <= self.tol_x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    fn rosenbrock(x: &Array1<f64>) -> f64 {
+        (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0].powi(2)).powi(2)
+    }
+
+    #[test]
+    fn unbounded() {
+        let result = NelderMead::default()
+            .minimize(rosenbrock, &arr1(&[-1.2, 1.0]), None)
+            .unwrap();
+        assert!(result.converged);
+        assert!((result.parameters[0] - 1.0).abs() < 1e-4);
+        assert!((result.parameters[1] - 1.0).abs() < 1e-4);
+        assert_eq!(result.history.len(), result.iterations + 1);
+        assert!(result.history.windows(2).all(|w| w[1].1 <= w[0].1));
+    }
+
+    #[test]
+    fn bounded() {
+        // the unconstrained minimum at (2, -1) is outside of the bounds
+        let f = |x: &Array1<f64>| (x[0] - 2.0).powi(2) + (x[1] + 1.0).powi(2);
+        let bounds = Bounds::new(arr1(&[0.0, 0.0]), arr1(&[1.0, 1.0])).unwrap();
+        let result = NelderMead::default()
+            .minimize(f, &arr1(&[0.5, 0.5]), Some(&bounds))
+            .unwrap();
+        assert!((result.parameters[0] - 1.0).abs() < 1e-6);
+        assert!(result.parameters[1].abs() < 1e-6);
+    }
+
+    #[test]
+    fn failed_evaluations() {
+        // the objective function is undefined for negative parameters
+        let f = |x: &Array1<f64>| (x[0].sqrt() - 1.0).powi(2);
+        let result = NelderMead::default()
+            .initial_step(2.0)
+            .minimize(f, &arr1(&[0.5]), None)
+            .unwrap();
+        assert!(result.converged);
+        assert!((result.parameters[0] - 1.0).abs() < 1e-4);
+    }
+}
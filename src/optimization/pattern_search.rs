@@ -0,0 +1,198 @@
+use super::{initial_parameters, initial_step, Bounds, Objective, OptimizationResult};
+use crate::EstimatorError;
+use ndarray::Array1;
+
+/// Pattern search according to Hooke and Jeeves.
+///
+/// In every iteration, each parameter is varied by its step size. If
+/// this exploration improves the objective function, a pattern move along
+/// the direction of improvement is attempted, otherwise the step sizes
+/// are reduced. Bounds are handled by projecting all trial points onto the bounds.
+#[derive(Clone, Copy, Debug)]
+pub struct PatternSearch {
+    /// maximum number of iterations
+    pub max_iter: usize,
+    /// tolerance for the step sizes relative to the parameters
+    pub tol_x: f64,
+    /// initial step sizes relative to the initial parameters
+    pub initial_step: f64,
+    /// factor by which the step sizes are reduced
+    pub contraction: f64,
+}
+
+impl Default for PatternSearch {
+    fn default() -> Self {
+        Self {
+            max_iter: 1000,
+            tol_x: 1e-8,
+            initial_step: 0.1,
+            contraction: 0.5,
+        }
+    }
+}
+
+impl PatternSearch {
+    /// Set the maximum number of iterations.
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Set the tolerance for the parameters.
+    pub fn tol_x(mut self, tol_x: f64) -> Self {
+        self.tol_x = tol_x;
+        self
+    }
+
+    /// Set the relative initial step sizes.
+    pub fn initial_step(mut self, initial_step: f64) -> Self {
+        self.initial_step = initial_step;
+        self
+    }
+
+    /// Set the factor by which the step sizes are reduced.
+    pub fn contraction(mut self, contraction: f64) -> Self {
+        self.contraction = contraction;
+        self
+    }
+
+    /// Minimize `f` starting from `x0`.
+    pub fn minimize<F: FnMut(&Array1<f64>) -> f64>(
+        &self,
+        f: F,
+        x0: &Array1<f64>,
+        bounds: Option<&Bounds>,
+    ) -> Result<OptimizationResult, EstimatorError> {
+        let mut objective = Objective::new(f);
+        let mut x = initial_parameters(x0, bounds)?;
+        let mut fx = objective.evaluate(&x);
+        let mut step = x.mapv(|xi| initial_step(xi, self.initial_step));
+
+        let mut history = vec![(x.clone(), fx)];
+        let mut converged = false;
+        let mut iterations = 0;
+        while iterations < self.max_iter {
+            let max_step = step
+                .iter()
+                .zip(x.iter())
+                .map(|(s, xi)| s / xi.abs().max(1.0))
+                .fold(0.0, f64::max);
+            if fx.is_finite() && max_step <= self.tol_x {
+                converged = true;
+                break;
+            }
+            iterations += 1;
+
+            let (mut xe, mut fe) = explore(&mut objective, &x, fx, &step, bounds);
+            if fe < fx {
+                // pattern moves along the direction of improvement
+                // as long as the objective function decreases
+                loop {
+                    let xp = project(&xe + &(&xe - &x), bounds);
+                    x = xe;
+                    fx = fe;
+                    history.push((x.clone(), fx));
+                    if iterations == self.max_iter {
+                        break;
+                    }
+                    let fp = objective.evaluate(&xp);
+                    let (xpe, fpe) = explore(&mut objective, &xp, fp, &step, bounds);
+                    if fpe >= fx {
+                        break;
+                    }
+                    xe = xpe;
+                    fe = fpe;
+                    iterations += 1;
+                }
+            } else {
+                step *= self.contraction;
+                history.push((x.clone(), fx));
+            }
+        }
+        Ok(OptimizationResult {
+            parameters: x,
+            cost: fx,
+            iterations,
+            evaluations: objective.evaluations,
+            converged,
+            history,
+        })
+    }
+}
+
+fn project(x: Array1<f64>, bounds: Option<&Bounds>) -> Array1<f64> {
+    match bounds {
+        Some(b) => b.clip(&x),
+        None => x,
+    }
+}
+
+/// Vary each parameter by its step size in both directions and
+/// keep every improvement.
+fn explore<F: FnMut(&Array1<f64>) -> f64>(
+    objective: &mut Objective<F>,
+    x: &Array1<f64>,
+    fx: f64,
+    step: &Array1<f64>,
+    bounds: Option<&Bounds>,
+) -> (Array1<f64>, f64) {
+    let mut x = x.clone();
+    let mut fx = fx;
+    for i in 0..x.len() {
+        for &direction in [1.0, -1.0].iter() {
+            let mut xt = x.clone();
+            xt[i] += direction * step[i];
+            let xt = project(xt, bounds);
+            if xt[i] == x[i] {
+                continue;
+            }
+            let ft = objective.evaluate(&xt);
+            if ft < fx {
+                x = xt;
+                fx = ft;
+                break;
+            }
+        }
+    }
+    (x, fx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    #[test]
+    fn quadratic() {
+        let f = |x: &Array1<f64>| (x[0] - 1.0).powi(2) + 10.0 * (x[1] + 2.0).powi(2);
+        let result = PatternSearch::default()
+            .minimize(f, &arr1(&[0.0, 0.0]), None)
+            .unwrap();
+        assert!(result.converged);
+        assert!((result.parameters[0] - 1.0).abs() < 1e-6);
+        assert!((result.parameters[1] + 2.0).abs() < 1e-6);
+        assert!(result.history.windows(2).all(|w| w[1].1 <= w[0].1));
+    }
+
+    #[test]
+    fn bounded() {
+        let f = |x: &Array1<f64>| (x[0] - 2.0).powi(2) + (x[1] + 1.0).powi(2);
+        let bounds = Bounds::new(arr1(&[0.0, 0.0]), arr1(&[1.0, 1.0])).unwrap();
+        let result = PatternSearch::default()
+            .minimize(f, &arr1(&[0.5, 0.5]), Some(&bounds))
+            .unwrap();
+        assert!((result.parameters[0] - 1.0).abs() < 1e-6);
+        assert!(result.parameters[1].abs() < 1e-6);
+    }
+
+    #[test]
+    fn max_iter() {
+        let f = |x: &Array1<f64>| (x[0] - 1.0).powi(2);
+        let result = PatternSearch::default()
+            .max_iter(3)
+            .minimize(f, &arr1(&[100.0]), None)
+            .unwrap();
+        assert!(!result.converged);
+        assert_eq!(result.iterations, 3);
+    }
+}
@@ -0,0 +1,268 @@
+use super::{sum_of_squares, Bounds, NelderMead};
+use crate::EstimatorError;
+use ndarray::{Array1, Array2};
+
+/// Profile likelihood of a single parameter.
+///
+/// The parameter is fixed at every value of a grid while the remaining
+/// parameters are optimized with the Nelder-Mead algorithm. Starting at
+/// the optimum, the grid is traversed in both directions and every
+/// optimization is initialized with the result of the neighbouring grid point.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProfileLikelihood {
+    /// optimizer of the remaining parameters
+    pub local: NelderMead,
+}
+
+impl ProfileLikelihood {
+    /// Set the optimizer of the remaining parameters.
+    pub fn local(mut self, local: NelderMead) -> Self {
+        self.local = local;
+        self
+    }
+
+    /// Profile parameter `index` of the least squares problem with
+    /// the given `residuals` on `grid`.
+    ///
+    /// `optimum` are the optimal parameters of the full problem. If `bounds`
+    /// are given, the remaining parameters are optimized within them.
+    pub fn profile<F>(
+        &self,
+        mut residuals: F,
+        optimum: &Array1<f64>,
+        index: usize,
+        grid: &Array1<f64>,
+        bounds: Option<&Bounds>,
+    ) -> Result<Profile, EstimatorError>
+    where
+        F: FnMut(&Array1<f64>) -> Result<Array1<f64>, EstimatorError>,
+    {
+        let n = optimum.len();
+        if index >= n || grid.is_empty() || matches!(bounds, Some(b) if b.len() != n) {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let r = residuals(optimum)?;
+        let datapoints = r.len();
+        let min_cost = sum_of_squares(Ok(r));
+        let reduced_bounds = bounds
+            .map(|b| Bounds::new(remove(&b.lower, index), remove(&b.upper, index)))
+            .transpose()?;
+
+        let mut values = grid.to_vec();
+        values.sort_by(f64::total_cmp);
+        let split = values.partition_point(|&v| v < optimum[index]);
+        let mut points = vec![(Array1::zeros(n), f64::INFINITY, false); values.len()];
+        let below = (0..split).rev();
+        let above = split..values.len();
+        for side in [below.collect::<Vec<_>>(), above.collect()] {
+            let mut x0 = remove(optimum, index);
+            for i in side {
+                let value = values[i];
+                let mut objective =
+                    |y: &Array1<f64>| sum_of_squares(residuals(&insert(y, index, value)));
+                let (y, cost, converged) = if n == 1 {
+                    (x0.clone(), objective(&x0), true)
+                } else {
+                    let r = self
+                        .local
+                        .minimize(objective, &x0, reduced_bounds.as_ref())?;
+                    (r.parameters, r.cost, r.converged)
+                };
+                points[i] = (insert(&y, index, value), cost, converged);
+                x0 = y;
+            }
+        }
+
+        let mut parameters = Array2::zeros((values.len(), n));
+        for (mut row, (x, _, _)) in parameters.outer_iter_mut().zip(points.iter()) {
+            row.assign(x);
+        }
+        let cost: Array1<f64> = points.iter().map(|p| p.1).collect();
+        Ok(Profile {
+            index,
+            optimum: optimum[index],
+            min_cost: cost.iter().fold(min_cost, |a, &b| a.min(b)),
+            datapoints,
+            values: Array1::from_vec(values),
+            cost,
+            parameters,
+            converged: points.iter().map(|p| p.2).collect(),
+        })
+    }
+}
+
+/// Result of a profile likelihood calculation.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    /// index of the profiled parameter
+    pub index: usize,
+    /// optimal value of the profiled parameter
+    pub optimum: f64,
+    /// minimum sum of squares of the residuals
+    pub min_cost: f64,
+    /// number of residuals
+    pub datapoints: usize,
+    /// sorted grid of the profiled parameter
+    pub values: Array1<f64>,
+    /// minimum sum of squares of the residuals at every grid point
+    pub cost: Array1<f64>,
+    /// all parameters at every grid point (one row per grid point)
+    pub parameters: Array2<f64>,
+    /// `true` if the optimization at the grid point converged
+    pub converged: Vec<bool>,
+}
+
+impl Profile {
+    /// Returns the likelihood ratio statistic `N ln(S / S_min)` at every grid point,
+    /// where `S` is the sum of squares and `N` the number of residuals.
+    ///
+    /// Assuming normally distributed residuals with unknown variance, the statistic
+    /// is asymptotically chi-squared distributed with one degree of freedom.
+    pub fn likelihood_ratio(&self) -> Array1<f64> {
+        self.cost
+            .mapv(|s| self.datapoints as f64 * (s / self.min_cost).ln())
+    }
+
+    /// Returns the lower and upper bound of the likelihood-based
+    /// confidence interval of the profiled parameter.
+    ///
+    /// The bounds are the values at which the likelihood ratio exceeds
+    /// `threshold`, interpolated linearly between grid points, e.g.,
+    /// 3.84 for a 95% confidence interval and 2.71 for 90%.
+    /// A bound is `None` if the profile does not exceed the
+    /// threshold on that side of the optimum within the grid.
+    pub fn confidence_interval(&self, threshold: f64) -> (Option<f64>, Option<f64>) {
+        let ratio = self.likelihood_ratio();
+        let split = self.values.iter().filter(|&&v| v < self.optimum).count();
+        let crossing = |indices: &mut dyn Iterator<Item = usize>| {
+            let (mut v0, mut r0) = (self.optimum, 0.0);
+            for i in indices {
+                let (v1, r1) = (self.values[i], ratio[i]);
+                if r1 >= threshold {
+                    let t = if r1.is_finite() {
+                        (threshold - r0) / (r1 - r0)
+                    } else {
+                        0.0
+                    };
+                    return Some(v0 + t * (v1 - v0));
+                }
+                v0 = v1;
+                r0 = r1;
+            }
+            None
+        };
+        (
+            crossing(&mut (0..split).rev()),
+            crossing(&mut (split..self.values.len())),
+        )
+    }
+
+    /// Representation as markdown table.
+    pub fn _repr_markdown_(&self) -> String {
+        let mut f = format!(
+            "| parameter {} | cost | likelihood ratio | converged |\n|-:|-:|-:|:-:|",
+            self.index
+        );
+        for ((v, c), (r, conv)) in self
+            .values
+            .iter()
+            .zip(self.cost.iter())
+            .zip(self.likelihood_ratio().iter().zip(self.converged.iter()))
+        {
+            f += &format!("\n|{}|{:e}|{}|{}|", v, c, r, conv);
+        }
+        f
+    }
+}
+
+/// Copy of `x` without the element at `index`.
+fn remove(x: &Array1<f64>, index: usize) -> Array1<f64> {
+    x.iter()
+        .enumerate()
+        .filter(|&(i, _)| i != index)
+        .map(|(_, &x)| x)
+        .collect()
+}
+
+/// Copy of `y` with `value` inserted at `index`.
+fn insert(y: &Array1<f64>, index: usize, value: f64) -> Array1<f64> {
+    let mut x = y.to_vec();
+    x.insert(index, value);
+    Array1::from_vec(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    /// Residuals of the straight line fit `y = a + b x`.
+    fn line(p: &Array1<f64>) -> Result<Array1<f64>, EstimatorError> {
+        let x = arr1(&[0.0, 1.0, 2.0, 3.0, 4.0]);
+        let y = arr1(&[1.1, 2.9, 5.2, 6.8, 9.1]);
+        Ok(x * p[1] + p[0] - y)
+    }
+
+    /// Optimal parameters of the straight line fit, i.e.,
+    /// `b = S_xy / S_xx` and `a = mean(y) - b mean(x)`.
+    fn optimum() -> Array1<f64> {
+        arr1(&[1.04, 1.99])
+    }
+
+    #[test]
+    fn quadratic_profile() {
+        let optimum = optimum();
+        let r = line(&optimum).unwrap();
+        let min_cost = r.dot(&r);
+        let grid = Array1::linspace(1.5, 2.5, 201);
+        let local = NelderMead::default().tol_f(1e-14).tol_x(1e-10);
+        let profile = ProfileLikelihood::default()
+            .local(local)
+            .profile(line, &optimum, 1, &grid, None)
+            .unwrap();
+        assert_eq!(profile.datapoints, 5);
+        assert!(profile.converged.iter().all(|&c| c));
+        // S(b) = S_min + S_xx (b - b_opt)^2 with S_xx = 10
+        for (b, s) in profile.values.iter().zip(profile.cost.iter()) {
+            assert!((s - min_cost - 10.0 * (b - 1.99).powi(2)).abs() < 1e-8);
+        }
+        for (row, b) in profile.parameters.outer_iter().zip(profile.values.iter()) {
+            assert_eq!(row[1], *b);
+            assert!((row[0] - (5.02 - 2.0 * b)).abs() < 1e-4);
+        }
+
+        let threshold = 3.84;
+        let width = (min_cost * ((threshold / 5.0f64).exp() - 1.0) / 10.0).sqrt();
+        let (lower, upper) = profile.confidence_interval(threshold);
+        assert!((lower.unwrap() - (1.99 - width)).abs() < 1e-3);
+        assert!((upper.unwrap() - (1.99 + width)).abs() < 1e-3);
+        let (lower, upper) = profile.confidence_interval(1e3);
+        assert!(lower.is_none() && upper.is_none());
+    }
+
+    #[test]
+    fn single_parameter() {
+        let residuals = |p: &Array1<f64>| Ok(arr1(&[p[0] - 1.0, p[0] - 3.0]));
+        let grid = arr1(&[3.0, 1.0, 2.0]);
+        let profile = ProfileLikelihood::default()
+            .profile(residuals, &arr1(&[2.0]), 0, &grid, None)
+            .unwrap();
+        assert_eq!(profile.values, arr1(&[1.0, 2.0, 3.0]));
+        assert_eq!(profile.cost, arr1(&[4.0, 2.0, 4.0]));
+        assert_eq!(profile.min_cost, 2.0);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let result = ProfileLikelihood::default().profile(line, &optimum(), 2, &arr1(&[1.0]), None);
+        assert!(matches!(result, Err(EstimatorError::IncompatibleInput)));
+    }
+
+    #[test]
+    fn remove_and_insert() {
+        let x = arr1(&[1.0, 2.0, 3.0]);
+        let y = remove(&x, 1);
+        assert_eq!(y, arr1(&[1.0, 3.0]));
+        assert_eq!(insert(&y, 1, 2.0), x);
+    }
+}
@@ -0,0 +1,273 @@
+use super::OptimizationResult;
+use crate::EstimatorError;
+use ndarray::{Array1, Array2, Zip};
+
+/// Transformation of a single parameter between the physical
+/// value and the value seen by the optimizer.
+#[derive(Clone, Copy, Debug)]
+pub enum Transformation {
+    /// The parameter is optimized directly.
+    Identity,
+    /// The logarithm of a positive parameter is optimized.
+    Log,
+    /// The logit of a parameter within `(lower, upper)` is optimized,
+    /// i.e., the physical parameter is a sigmoid function of the
+    /// optimized value.
+    Logit { lower: f64, upper: f64 },
+}
+
+impl Transformation {
+    /// Returns the transformation of a parameter bounded by `lower` and `upper`.
+    pub fn bounded(lower: f64, upper: f64) -> Self {
+        Self::Logit { lower, upper }
+    }
+
+    /// Value seen by the optimizer. Returns `None` if the parameter is
+    /// outside the domain of the transformation.
+    fn to_internal(self, p: f64) -> Option<f64> {
+        match self {
+            Self::Identity => Some(p),
+            Self::Log if p > 0.0 => Some(p.ln()),
+            Self::Logit { lower, upper } if p > lower && p < upper => {
+                let z = (p - lower) / (upper - lower);
+                Some((z / (1.0 - z)).ln())
+            }
+            _ => None,
+        }
+    }
+
+    /// Physical value of the parameter.
+    fn to_physical(self, y: f64) -> f64 {
+        match self {
+            Self::Identity => y,
+            Self::Log => y.exp(),
+            Self::Logit { lower, upper } => lower + (upper - lower) / (1.0 + (-y).exp()),
+        }
+    }
+
+    /// Derivative of the physical value with respect to the value seen by the optimizer.
+    fn derivative(self, y: f64) -> f64 {
+        match self {
+            Self::Identity => 1.0,
+            Self::Log => y.exp(),
+            Self::Logit { lower, upper } => {
+                let z = 1.0 / (1.0 + (-y).exp());
+                (upper - lower) * z * (1.0 - z)
+            }
+        }
+    }
+}
+
+/// Transformations of all parameters of an optimization.
+///
+/// The optimizers work with the transformed parameters. Results,
+/// Jacobians and covariances can be transformed back to the
+/// physical parameters.
+#[derive(Clone, Debug)]
+pub struct ParameterTransformation {
+    transformations: Vec<Transformation>,
+}
+
+impl ParameterTransformation {
+    /// Create a new transformation from the transformations of each parameter.
+    pub fn new(transformations: Vec<Transformation>) -> Self {
+        Self { transformations }
+    }
+
+    /// Returns the number of parameters.
+    pub fn len(&self) -> usize {
+        self.transformations.len()
+    }
+
+    /// Returns `true` if there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.transformations.is_empty()
+    }
+
+    /// Transform physical parameters to the parameters seen by the optimizer.
+    pub fn to_internal(&self, parameters: &Array1<f64>) -> Result<Array1<f64>, EstimatorError> {
+        if parameters.len() != self.len() {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        self.transformations
+            .iter()
+            .zip(parameters.iter())
+            .enumerate()
+            .map(|(i, (t, &p))| t.to_internal(p).ok_or(EstimatorError::OutOfDomain(i)))
+            .collect()
+    }
+
+    /// Transform the parameters seen by the optimizer to physical parameters.
+    pub fn to_physical(&self, parameters: &Array1<f64>) -> Array1<f64> {
+        self.transformations
+            .iter()
+            .zip(parameters.iter())
+            .map(|(t, &y)| t.to_physical(y))
+            .collect()
+    }
+
+    /// Derivatives of the physical parameters with respect to
+    /// the parameters seen by the optimizer.
+    pub fn derivatives(&self, parameters: &Array1<f64>) -> Array1<f64> {
+        self.transformations
+            .iter()
+            .zip(parameters.iter())
+            .map(|(t, &y)| t.derivative(y))
+            .collect()
+    }
+
+    /// Compose a function of the physical parameters with the transformation,
+    /// e.g., to obtain the objective function or the residuals in terms of
+    /// the parameters seen by the optimizer.
+    pub fn compose<'a, T, F>(&'a self, f: F) -> impl Fn(&Array1<f64>) -> T + 'a
+    where
+        F: Fn(&Array1<f64>) -> T + 'a,
+    {
+        move |y| f(&self.to_physical(y))
+    }
+
+    /// Transform a Jacobian with respect to the parameters seen by the
+    /// optimizer at `parameters` into a Jacobian with respect to the
+    /// physical parameters.
+    pub fn jacobian_to_physical(
+        &self,
+        jacobian: &Array2<f64>,
+        parameters: &Array1<f64>,
+    ) -> Array2<f64> {
+        jacobian / &self.derivatives(parameters)
+    }
+
+    /// Transform a covariance matrix of the parameters seen by the
+    /// optimizer at `parameters` into the covariance matrix of the
+    /// physical parameters (linear error propagation).
+    pub fn covariance_to_physical(
+        &self,
+        covariance: &Array2<f64>,
+        parameters: &Array1<f64>,
+    ) -> Array2<f64> {
+        let d = self.derivatives(parameters);
+        let mut covariance = covariance.clone();
+        Zip::indexed(&mut covariance).for_each(|(i, j), c| *c *= d[i] * d[j]);
+        covariance
+    }
+
+    /// Transform the parameters of an optimization result and its
+    /// history to physical parameters.
+    pub fn result_to_physical(&self, result: OptimizationResult) -> OptimizationResult {
+        OptimizationResult {
+            parameters: self.to_physical(&result.parameters),
+            history: result
+                .history
+                .iter()
+                .map(|(y, f)| (self.to_physical(y), *f))
+                .collect(),
+            ..result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::jacobian;
+    use ndarray::arr1;
+
+    fn transformation() -> ParameterTransformation {
+        ParameterTransformation::new(vec![
+            Transformation::Identity,
+            Transformation::Log,
+            Transformation::bounded(1.0, 3.0),
+        ])
+    }
+
+    #[test]
+    fn round_trip() {
+        let t = transformation();
+        let p = arr1(&[-1.5, 0.2, 2.5]);
+        let y = t.to_internal(&p).unwrap();
+        assert_eq!(y[0], -1.5);
+        assert!((y[1] - 0.2f64.ln()).abs() < 1e-15);
+        assert!((y[2] - 3.0f64.ln()).abs() < 1e-15);
+        let p_back = t.to_physical(&y);
+        for (a, b) in p.iter().zip(p_back.iter()) {
+            assert!((a - b).abs() < 1e-14);
+        }
+    }
+
+    #[test]
+    fn out_of_domain() {
+        let t = transformation();
+        assert!(matches!(
+            t.to_internal(&arr1(&[0.0, -0.2, 2.0])),
+            Err(EstimatorError::OutOfDomain(1))
+        ));
+        assert!(matches!(
+            t.to_internal(&arr1(&[0.0, 0.2, 3.0])),
+            Err(EstimatorError::OutOfDomain(2))
+        ));
+        assert!(matches!(
+            t.to_internal(&arr1(&[0.0, 0.2])),
+            Err(EstimatorError::IncompatibleInput)
+        ));
+    }
+
+    #[test]
+    fn derivatives() {
+        let t = transformation();
+        let y = arr1(&[0.3, -0.7, 0.4]);
+        let d = t.derivatives(&y);
+        let h = 1e-6;
+        for i in 0..3 {
+            let mut y_p = y.clone();
+            let mut y_m = y.clone();
+            y_p[i] += h;
+            y_m[i] -= h;
+            let fd = (t.to_physical(&y_p)[i] - t.to_physical(&y_m)[i]) / (2.0 * h);
+            assert!((d[i] - fd).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn jacobian_and_covariance() {
+        let t = transformation();
+        let residuals = |p: &Array1<f64>| -> Result<Array1<f64>, EstimatorError> {
+            Ok(arr1(&[p[0] * p[1], p[1] + p[2].powi(2), p[0] - p[2]]))
+        };
+        let p = arr1(&[0.5, 2.0, 1.5]);
+        let y = t.to_internal(&p).unwrap();
+        let jac_internal = jacobian(t.compose(residuals), &y).unwrap();
+        let jac_physical = jacobian(residuals, &p).unwrap();
+        let jac = t.jacobian_to_physical(&jac_internal, &y);
+        for (a, b) in jac.iter().zip(jac_physical.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+
+        let covariance = arr1(&[1.0, 2.0, 3.0]).into_shape((3, 1)).unwrap();
+        let covariance = covariance.dot(&covariance.t());
+        let d = t.derivatives(&y);
+        let c = t.covariance_to_physical(&covariance, &y);
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!((c[[i, j]] - covariance[[i, j]] * d[i] * d[j]).abs() < 1e-14);
+            }
+        }
+    }
+
+    #[test]
+    fn result() {
+        let t = transformation();
+        let y = arr1(&[1.0, 0.0, 0.0]);
+        let result = OptimizationResult {
+            parameters: y.clone(),
+            cost: 1.0,
+            iterations: 1,
+            evaluations: 2,
+            converged: true,
+            history: vec![(y.clone(), 1.0)],
+        };
+        let result = t.result_to_physical(result);
+        assert_eq!(result.parameters, arr1(&[1.0, 1.0, 2.0]));
+        assert_eq!(result.history[0].0, result.parameters);
+        assert_eq!(result.evaluations, 2);
+    }
+}
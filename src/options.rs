@@ -0,0 +1,101 @@
+use feos_core::SolverOptions;
+
+/// Solver options of the iterations in the prediction of a `DataSet`.
+///
+/// The default uses the default options of `feos_core` for every solver.
+///
+/// Density iterations (e.g. in `State::new_npt`) can not be configured:
+/// `feos_core` 0.2 uses a fixed maximum of 50 iterations and fixed
+/// tolerances, and its `density_iteration` module is not public.
+/// Only the phase of a density iteration can be selected by the
+/// `DensityInitialization` of the respective `DataSet`.
+#[derive(Clone, Copy, Default)]
+pub struct PredictionOptions {
+    /// options of the critical point iteration
+    pub critical_point: SolverOptions,
+    /// options of pure component phase equilibria and
+    /// of the outer iteration of bubble and dew points
+    pub phase_equilibrium: SolverOptions,
+    /// options of the inner iteration of bubble and dew points
+    pub phase_equilibrium_inner: SolverOptions,
+}
+
+impl PredictionOptions {
+    /// Set the options of the critical point iteration.
+    pub fn critical_point(mut self, options: SolverOptions) -> Self {
+        self.critical_point = options;
+        self
+    }
+
+    /// Set the options of pure component phase equilibria and
+    /// of the outer iteration of bubble and dew points.
+    pub fn phase_equilibrium(mut self, options: SolverOptions) -> Self {
+        self.phase_equilibrium = options;
+        self
+    }
+
+    /// Set the options of the inner iteration of bubble and dew points.
+    pub fn phase_equilibrium_inner(mut self, options: SolverOptions) -> Self {
+        self.phase_equilibrium_inner = options;
+        self
+    }
+
+    /// Options of bubble and dew point iterations in the order
+    /// expected by `feos_core` (inner, outer).
+    pub(crate) fn bubble_dew(&self) -> (SolverOptions, SolverOptions) {
+        (self.phase_equilibrium_inner, self.phase_equilibrium)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane;
+    use crate::{DataSet, Extrapolation, VaporPressure};
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    #[test]
+    fn builder() {
+        let options = PredictionOptions::default()
+            .critical_point(SolverOptions::new().max_iter(10))
+            .phase_equilibrium(SolverOptions::new().tol(1e-6))
+            .phase_equilibrium_inner(SolverOptions::new().max_iter(5));
+        assert_eq!(options.critical_point.max_iter, Some(10));
+        assert_eq!(options.phase_equilibrium.tol, Some(1e-6));
+        let (inner, outer) = options.bubble_dew();
+        assert_eq!(inner.max_iter, Some(5));
+        assert_eq!(outer.tol, Some(1e-6));
+        assert_eq!(
+            PredictionOptions::default().phase_equilibrium.max_iter,
+            None
+        );
+    }
+
+    #[test]
+    fn options_are_used() {
+        let eos = propane();
+        let vapor_pressure = |extrapolation, options| {
+            VaporPressure::new(
+                arr1(&[1e6, 5e6]) * PASCAL,
+                arr1(&[300.0, 400.0]) * KELVIN,
+                extrapolation,
+                options,
+            )
+            .unwrap()
+        };
+        let options = PredictionOptions::default();
+        let data = vapor_pressure(Extrapolation::ClausiusClapeyron, options);
+        let prediction = data.predict_with_status(&eos).unwrap();
+        assert!(prediction.failed().is_empty());
+
+        let options = options.phase_equilibrium(SolverOptions::new().max_iter(0));
+        let data = vapor_pressure(Extrapolation::None, options);
+        let prediction = data.predict_with_status(&eos).unwrap();
+        assert_eq!(prediction.failed().len(), 2);
+
+        let options = PredictionOptions::default().critical_point(SolverOptions::new().max_iter(0));
+        let data = vapor_pressure(Extrapolation::ClausiusClapeyron, options);
+        assert!(data.predict_with_status(&eos).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use super::{DataSet, EstimatorError};
+use feos_core::{EosUnit, EquationOfState};
+use ndarray::Array1;
+use quantity::{QuantityArray1, QuantityScalar};
+use std::fmt;
+use std::rc::Rc;
+
+/// Status of the prediction of a single data point.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PredictionStatus {
+    /// the equation of state was evaluated successfully
+    Converged,
+    /// the value was estimated by an extrapolation
+    Extrapolated,
+    /// the evaluation failed with the given message
+    Failed(String),
+}
+
+impl PredictionStatus {
+    /// Returns `true` if the prediction failed.
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+}
+
+impl fmt::Display for PredictionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Converged => write!(f, "converged"),
+            Self::Extrapolated => write!(f, "extrapolated"),
+            Self::Failed(message) => write!(f, "failed: {}", message),
+        }
+    }
+}
+
+/// Predictions of a `DataSet` together with the status of every data point.
+#[derive(Clone)]
+pub struct Prediction<U> {
+    /// predicted values (`NAN` for failed data points)
+    pub values: QuantityArray1<U>,
+    /// status of every data point
+    pub status: Vec<PredictionStatus>,
+}
+
+impl<U: EosUnit> Prediction<U> {
+    /// Create the status from the predicted values.
+    ///
+    /// `NAN` values are considered as failed.
+    pub fn from_values(values: QuantityArray1<U>) -> Self {
+        let status = (0..values.len())
+            .map(|i| {
+                if values.get(i).is_nan() {
+                    PredictionStatus::Failed("the prediction is NaN".to_owned())
+                } else {
+                    PredictionStatus::Converged
+                }
+            })
+            .collect();
+        Self { values, status }
+    }
+
+    /// Collect the results of the individual data points.
+    ///
+    /// Failed data points are set to `NAN` (in units of `unit`) and the
+    /// error message is stored in their status.
+    pub(crate) fn from_results<I, T>(
+        results: I,
+        unit: QuantityScalar<U>,
+    ) -> Result<Self, EstimatorError>
+    where
+        I: IntoIterator<Item = Result<QuantityScalar<U>, T>>,
+        T: fmt::Display,
+    {
+        let results: Vec<_> = results.into_iter().collect();
+        let mut values = Array1::zeros(results.len()) * unit;
+        let mut status = Vec::with_capacity(results.len());
+        for (i, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(value) => {
+                    values.try_set(i, value)?;
+                    status.push(PredictionStatus::Converged);
+                }
+                Err(e) => {
+                    values.try_set(i, f64::NAN * unit)?;
+                    status.push(PredictionStatus::Failed(e.to_string()));
+                }
+            }
+        }
+        Ok(Self { values, status })
+    }
+
+    /// Returns the indices and messages of all failed data points.
+    pub fn failed(&self) -> Vec<(usize, &str)> {
+        self.status
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| match s {
+                PredictionStatus::Failed(message) => Some((i, message.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Treatment of data points with a non-finite cost in an `Estimator`,
+/// e.g., because the evaluation of the equation of state failed.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum FailurePolicy {
+    /// keep the non-finite cost
+    #[default]
+    Propagate,
+    /// set the cost of the data point to zero
+    Skip,
+    /// replace the cost by a fixed residual that is normalized
+    /// by the number of data points (the loss function is not applied)
+    Penalize(f64),
+    /// return an error that contains the reason for the failure
+    Error,
+}
+
+impl FailurePolicy {
+    /// Apply the policy to the cost of `dataset`.
+    pub(crate) fn apply<U: EosUnit, E: EquationOfState>(
+        &self,
+        dataset: &dyn DataSet<U, E>,
+        eos: &Rc<E>,
+        cost: &mut Array1<f64>,
+    ) -> Result<(), EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        match *self {
+            Self::Propagate => (),
+            Self::Skip => cost.mapv_inplace(|c| if c.is_finite() { c } else { 0.0 }),
+            Self::Penalize(penalty) => {
+                let penalty = penalty / dataset.datapoints() as f64;
+                cost.mapv_inplace(|c| if c.is_finite() { c } else { penalty })
+            }
+            Self::Error => {
+                if let Some(i) = cost.iter().position(|c| !c.is_finite()) {
+                    let prediction = dataset.predict_with_status(eos)?;
+                    let (index, message) = match prediction.failed().first() {
+                        Some(&(j, message)) => (j, message.to_owned()),
+                        None => (i, "the cost function is not finite".to_owned()),
+                    };
+                    return Err(EstimatorError::PredictionFailed(
+                        dataset.target_str().to_owned(),
+                        index,
+                        message,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finite penalty for data points with a non-finite cost in an `Estimator`.
+///
+/// The residual of failed data points is `residual + slope * d`, where `d` is
+/// the Euclidean distance between the parameters and `reference`, relative to
+/// the magnitude of the reference (at least 1). Increasing the penalty with
+/// the distance from parameters for which the model works, e.g., the initial
+/// parameters, steers optimizers back from regions in which the model fails.
+#[derive(Clone, Debug)]
+pub struct FailurePenalty {
+    /// residual of failed data points at the reference parameters
+    pub residual: f64,
+    /// increase of the residual with the relative distance from the reference
+    pub slope: f64,
+    /// reference parameters
+    pub reference: Option<Array1<f64>>,
+}
+
+impl FailurePenalty {
+    /// Create a constant penalty.
+    pub fn new(residual: f64) -> Self {
+        Self {
+            residual,
+            slope: 0.0,
+            reference: None,
+        }
+    }
+
+    /// Increase the penalty with the distance from `reference`.
+    pub fn distance(mut self, reference: Array1<f64>, slope: f64) -> Self {
+        self.reference = Some(reference);
+        self.slope = slope;
+        self
+    }
+
+    /// Returns the residual of failed data points for the given parameters.
+    ///
+    /// If no parameters are given or their number does not match
+    /// the reference, the residual at the reference is returned.
+    pub fn residual(&self, parameters: Option<&Array1<f64>>) -> f64 {
+        match (&self.reference, parameters) {
+            (Some(reference), Some(x)) if reference.len() == x.len() => {
+                let distance = x
+                    .iter()
+                    .zip(reference.iter())
+                    .map(|(x, r)| ((x - r) / r.abs().max(1.0)).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                self.residual + self.slope * distance
+            }
+            _ => self.residual,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane;
+    use crate::{Loss, PredictionOptions, VaporPressure};
+    use feos_core::cubic::PengRobinson;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    /// Vapor pressures below and above the critical temperature of propane.
+    fn vapor_pressure() -> VaporPressure<SIUnit> {
+        VaporPressure::new(
+            arr1(&[1e6, 1e6]) * PASCAL,
+            arr1(&[300.0, 400.0]) * KELVIN,
+            Default::default(),
+            PredictionOptions::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn status() {
+        assert!(PredictionStatus::Failed("error".to_owned()).is_failed());
+        assert!(!PredictionStatus::Extrapolated.is_failed());
+        assert_eq!(
+            PredictionStatus::Failed("error".to_owned()).to_string(),
+            "failed: error"
+        );
+    }
+
+    #[test]
+    fn from_values() {
+        let prediction = Prediction::from_values(arr1(&[1.0, f64::NAN, 3.0]) * KELVIN);
+        assert_eq!(prediction.failed(), vec![(1, "the prediction is NaN")]);
+        assert_eq!(prediction.status[0], PredictionStatus::Converged);
+    }
+
+    #[test]
+    fn from_results() {
+        let results = vec![Err("no convergence"), Ok(2.0 * KELVIN)];
+        let prediction = Prediction::from_results(results, KELVIN).unwrap();
+        assert!(prediction.values.get(0).is_nan());
+        assert_eq!(prediction.values.get(1), 2.0 * KELVIN);
+        assert_eq!(prediction.failed(), vec![(0, "no convergence")]);
+        assert!(Prediction::from_results(vec![Ok::<_, String>(2.0 * PASCAL)], KELVIN).is_err());
+    }
+
+    #[test]
+    fn vapor_pressure_status() {
+        let prediction = vapor_pressure().predict_with_status(&propane()).unwrap();
+        assert_eq!(prediction.status[0], PredictionStatus::Converged);
+        assert!(prediction.status[1].is_failed());
+        assert!(prediction.values.get(1).is_nan());
+    }
+
+    #[test]
+    fn policies() {
+        let eos = propane();
+        let data = vapor_pressure();
+        let cost = DataSet::<SIUnit, PengRobinson>::cost(&data, &eos, Loss::Linear).unwrap();
+        assert!(cost[0].is_finite() && cost[1].is_nan());
+        let apply = |policy: FailurePolicy| {
+            let mut c = cost.clone();
+            policy.apply(&data, &eos, &mut c).map(|_| c)
+        };
+        assert!(apply(FailurePolicy::Propagate).unwrap()[1].is_nan());
+        assert_eq!(apply(FailurePolicy::Skip).unwrap(), arr1(&[cost[0], 0.0]));
+        assert_eq!(
+            apply(FailurePolicy::Penalize(3.0)).unwrap(),
+            arr1(&[cost[0], 1.5])
+        );
+        match apply(FailurePolicy::Error) {
+            Err(EstimatorError::PredictionFailed(target, 1, _)) => {
+                assert_eq!(target, "vapor pressure")
+            }
+            _ => panic!("expected a failed prediction"),
+        }
+    }
+
+    #[test]
+    fn penalty() {
+        let penalty = FailurePenalty::new(2.0);
+        assert_eq!(penalty.residual(Some(&arr1(&[5.0]))), 2.0);
+
+        let penalty = penalty.distance(arr1(&[4.0, 0.5]), 3.0);
+        assert_eq!(penalty.residual(None), 2.0);
+        assert_eq!(penalty.residual(Some(&arr1(&[4.0, 0.5]))), 2.0);
+        // distances are relative to the reference but at least absolute
+        assert_eq!(penalty.residual(Some(&arr1(&[2.0, 0.5]))), 3.5);
+        assert_eq!(penalty.residual(Some(&arr1(&[4.0, 1.5]))), 5.0);
+        assert_eq!(penalty.residual(Some(&arr1(&[1.0]))), 2.0);
+    }
+}
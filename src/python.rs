@@ -1,10 +1,94 @@
-use crate::EstimatorError;
-use pyo3::exceptions::PyRuntimeError;
-use pyo3::PyErr;
+use crate::{BinaryVLECost, EstimatorError, Extrapolation};
+use feos_core::{DensityInitialization, EosUnit, SolverOptions, Verbosity};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::{PyAny, PyErr, PyResult};
 
 impl From<EstimatorError> for PyErr {
     fn from(e: EstimatorError) -> PyErr {
-        PyRuntimeError::new_err(e.to_string())
+        match e {
+            EstimatorError::PyError(e) => e,
+            e => PyRuntimeError::new_err(e.to_string()),
+        }
+    }
+}
+
+/// Parse the phase hint for the density iteration.
+#[doc(hidden)]
+pub fn density_initialization<U: EosUnit>(
+    density_initialization: Option<&str>,
+) -> PyResult<DensityInitialization<U>> {
+    match density_initialization {
+        Some("vapor") => Ok(DensityInitialization::Vapor),
+        Some("liquid") => Ok(DensityInitialization::Liquid),
+        None => Ok(DensityInitialization::None),
+        _ => Err(PyValueError::new_err(
+            "`density_initialization` must be 'vapor' or 'liquid'.",
+        )),
+    }
+}
+
+/// Parse the options of a single solver.
+#[doc(hidden)]
+pub fn solver_options(
+    max_iter: Option<usize>,
+    tol: Option<f64>,
+    verbosity: Option<&str>,
+) -> PyResult<SolverOptions> {
+    let verbosity = match verbosity {
+        None | Some("none") => Verbosity::None,
+        Some("result") => Verbosity::Result,
+        Some("iter") => Verbosity::Iter,
+        _ => {
+            return Err(PyValueError::new_err(
+                "`verbosity` must be 'none', 'result' or 'iter'.",
+            ))
+        }
+    };
+    Ok((max_iter, tol, Some(verbosity)).into())
+}
+
+/// Parse the extrapolation of the vapor pressure.
+#[doc(hidden)]
+pub fn extrapolation(
+    extrapolate: Option<&PyAny>,
+    points: Option<usize>,
+    slope: Option<f64>,
+) -> PyResult<Extrapolation> {
+    let extrapolate = match extrapolate {
+        None => return Ok(Extrapolation::None),
+        Some(e) => e,
+    };
+    if let Ok(extrapolate) = extrapolate.extract::<bool>() {
+        return Ok(if extrapolate {
+            Extrapolation::ClausiusClapeyron
+        } else {
+            Extrapolation::None
+        });
+    }
+    match extrapolate.extract::<&str>() {
+        Ok("none") => Ok(Extrapolation::None),
+        Ok("clausius-clapeyron") => Ok(match points {
+            Some(points) => Extrapolation::ClausiusClapeyronFit(points),
+            None => Extrapolation::ClausiusClapeyron,
+        }),
+        Ok("antoine") => Ok(Extrapolation::Antoine),
+        Ok("penalty") => Ok(Extrapolation::Penalty(slope.unwrap_or(1.0))),
+        _ => Err(PyValueError::new_err(
+            "`extrapolate` must be a bool or 'none', 'clausius-clapeyron', 'antoine' or 'penalty'.",
+        )),
+    }
+}
+
+/// Parse the cost function of the binary VLE data sets.
+#[doc(hidden)]
+pub fn binary_vle_cost(cost_function: Option<&str>) -> PyResult<BinaryVLECost> {
+    match cost_function {
+        None | Some("relative_difference") => Ok(BinaryVLECost::RelativeDifference),
+        Some("distance") => Ok(BinaryVLECost::Distance),
+        Some("chemical_potential") => Ok(BinaryVLECost::ChemicalPotential),
+        _ => Err(PyValueError::new_err(
+            "`cost_function` must be 'relative_difference', 'distance' or 'chemical_potential'.",
+        )),
     }
 }
 
@@ -111,6 +195,202 @@ macro_rules! impl_estimator {
             }
         }
 
+        /// Statistics of the relative differences between the predictions
+        /// of an equation of state and the target values of a ``DataSet``.
+        ///
+        /// Failed predictions (NaN) are only counted in `failed` and
+        /// excluded from all other statistics.
+        #[pyclass(name = "DeviationStatistics", unsendable)]
+        #[derive(Clone)]
+        pub struct PyDeviationStatistics(DeviationStatistics);
+
+        #[pymethods]
+        impl PyDeviationStatistics {
+            /// Number of data points.
+            #[getter]
+            fn get_datapoints(&self) -> usize {
+                self.0.datapoints
+            }
+
+            /// Number of data points for which the prediction failed.
+            #[getter]
+            fn get_failed(&self) -> usize {
+                self.0.failed
+            }
+
+            /// Mean relative difference (bias).
+            #[getter]
+            fn get_mrd(&self) -> f64 {
+                self.0.mrd
+            }
+
+            /// Mean absolute relative difference.
+            #[getter]
+            fn get_mard(&self) -> f64 {
+                self.0.mard
+            }
+
+            /// Root mean square of the relative differences.
+            #[getter]
+            fn get_rms(&self) -> f64 {
+                self.0.rms
+            }
+
+            /// Median of the absolute relative differences.
+            #[getter]
+            fn get_median(&self) -> f64 {
+                self.0.median
+            }
+
+            /// Maximum absolute relative difference.
+            #[getter]
+            fn get_max(&self) -> f64 {
+                self.0.max
+            }
+
+            /// Index of the data point with the maximum absolute relative difference.
+            #[getter]
+            fn get_max_index(&self) -> Option<usize> {
+                self.0.max_index
+            }
+
+            /// Tolerance of the absolute relative difference.
+            #[getter]
+            fn get_tolerance(&self) -> f64 {
+                self.0.tolerance
+            }
+
+            /// Fraction of all data points with an absolute relative
+            /// difference smaller than or equal to the tolerance.
+            #[getter]
+            fn get_within_tolerance(&self) -> f64 {
+                self.0.within_tolerance
+            }
+
+            fn __repr__(&self) -> PyResult<String> {
+                Ok(self.0.to_string())
+            }
+        }
+
+        /// Solver options of the iterations in the prediction of a ``DataSet``.
+        ///
+        /// The given options are used for all solvers. Use the methods
+        /// to set the options of individual solvers.
+        ///
+        /// Parameters
+        /// ----------
+        /// max_iter : int, optional
+        ///     The maximum number of iterations.
+        /// tol : float, optional
+        ///     The solution tolerance.
+        /// verbosity : str, optional
+        ///     'none', 'result' or 'iter'.
+        ///
+        /// Returns
+        /// -------
+        /// PredictionOptions
+        #[pyclass(name = "PredictionOptions", unsendable)]
+        #[pyo3(text_signature = "(max_iter=None, tol=None, verbosity=None)")]
+        #[derive(Clone)]
+        pub struct PyPredictionOptions(PredictionOptions);
+
+        #[pymethods]
+        impl PyPredictionOptions {
+            #[new]
+            fn new(
+                max_iter: Option<usize>,
+                tol: Option<f64>,
+                verbosity: Option<&str>,
+            ) -> PyResult<Self> {
+                let options = $crate::python::solver_options(max_iter, tol, verbosity)?;
+                Ok(Self(PredictionOptions {
+                    critical_point: options,
+                    phase_equilibrium: options,
+                    phase_equilibrium_inner: options,
+                }))
+            }
+
+            /// Return a copy with the given options of the critical point iteration.
+            ///
+            /// Parameters
+            /// ----------
+            /// max_iter : int, optional
+            ///     The maximum number of iterations.
+            /// tol : float, optional
+            ///     The solution tolerance.
+            /// verbosity : str, optional
+            ///     'none', 'result' or 'iter'.
+            ///
+            /// Returns
+            /// -------
+            /// PredictionOptions
+            #[pyo3(text_signature = "($self, max_iter=None, tol=None, verbosity=None)")]
+            fn critical_point(
+                &self,
+                max_iter: Option<usize>,
+                tol: Option<f64>,
+                verbosity: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(self.0.critical_point(
+                    $crate::python::solver_options(max_iter, tol, verbosity)?,
+                )))
+            }
+
+            /// Return a copy with the given options of pure component phase
+            /// equilibria and of the outer iteration of bubble and dew points.
+            ///
+            /// Parameters
+            /// ----------
+            /// max_iter : int, optional
+            ///     The maximum number of iterations.
+            /// tol : float, optional
+            ///     The solution tolerance.
+            /// verbosity : str, optional
+            ///     'none', 'result' or 'iter'.
+            ///
+            /// Returns
+            /// -------
+            /// PredictionOptions
+            #[pyo3(text_signature = "($self, max_iter=None, tol=None, verbosity=None)")]
+            fn phase_equilibrium(
+                &self,
+                max_iter: Option<usize>,
+                tol: Option<f64>,
+                verbosity: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(self.0.phase_equilibrium(
+                    $crate::python::solver_options(max_iter, tol, verbosity)?,
+                )))
+            }
+
+            /// Return a copy with the given options of the
+            /// inner iteration of bubble and dew points.
+            ///
+            /// Parameters
+            /// ----------
+            /// max_iter : int, optional
+            ///     The maximum number of iterations.
+            /// tol : float, optional
+            ///     The solution tolerance.
+            /// verbosity : str, optional
+            ///     'none', 'result' or 'iter'.
+            ///
+            /// Returns
+            /// -------
+            /// PredictionOptions
+            #[pyo3(text_signature = "($self, max_iter=None, tol=None, verbosity=None)")]
+            fn phase_equilibrium_inner(
+                &self,
+                max_iter: Option<usize>,
+                tol: Option<f64>,
+                verbosity: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(self.0.phase_equilibrium_inner(
+                    $crate::python::solver_options(max_iter, tol, verbosity)?,
+                )))
+            }
+        }
+
         /// A collection of experimental data that can be used to compute
         /// cost functions and make predictions using an equation of state.
         #[pyclass(name = "DataSet", unsendable)]
@@ -166,6 +446,28 @@ macro_rules! impl_estimator {
                 Ok(self.0.predict(&eos.0)?.into())
             }
 
+            /// Return the prediction of the equation of state together
+            /// with the status of every data point.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            ///
+            /// Returns
+            /// -------
+            /// (SIArray1, List[str])
+            ///     The predictions and the status of every data point:
+            ///     'converged', 'extrapolated' or 'failed: <reason>'.
+            #[pyo3(text_signature = "($self, eos)")]
+            fn predict_with_status(&self, eos: &$py_eos) -> PyResult<(PySIArray1, Vec<String>)> {
+                let prediction = self.0.predict_with_status(&eos.0)?;
+                Ok((
+                    prediction.values.into(),
+                    prediction.status.iter().map(|s| s.to_string()).collect(),
+                ))
+            }
+
             /// Return the relative difference between experimental data
             /// and prediction of the equation of state.
             ///
@@ -217,25 +519,40 @@ macro_rules! impl_estimator {
             ///     Experimental data for vapor pressure.
             /// temperature : SIArray1
             ///     Temperature for experimental data points.
-            /// extrapolate : bool, optional
-            ///     Use Antoine type equation to extrapolate vapor
-            ///     pressure if experimental data is above critial
-            ///     point of model. Defaults to False.
+            /// extrapolate : bool or str, optional
+            ///     Estimate the vapor pressure if experimental data is
+            ///     above the critical point of the model:
+            ///     'clausius-clapeyron' (or True), 'antoine', 'penalty'
+            ///     or 'none' (or False). Defaults to 'none'.
+            /// points : int, optional
+            ///     Fit the Clausius-Clapeyron equation to the given number
+            ///     of model points instead of using two points.
+            /// slope : float, optional
+            ///     Slope of ln(p) over (T - Tc) / Tc for 'penalty'.
+            ///     Defaults to 1.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the critical point and phase equilibrium iterations.
             ///
             /// Returns
             /// -------
             /// ``DataSet``
             #[staticmethod]
-            #[pyo3(text_signature = "(target, temperature, extrapolate)")]
+            #[pyo3(
+                text_signature = "(target, temperature, extrapolate=None, points=None, slope=None, options=None)"
+            )]
             fn vapor_pressure(
                 target: &PySIArray1,
                 temperature: &PySIArray1,
-                extrapolate: Option<bool>,
+                extrapolate: Option<&PyAny>,
+                points: Option<usize>,
+                slope: Option<f64>,
+                options: Option<PyPredictionOptions>,
             ) -> PyResult<Self> {
                 Ok(Self(Rc::new(VaporPressure::<SIUnit>::new(
                     target.clone().into(),
                     temperature.clone().into(),
-                    extrapolate.unwrap_or(false),
+                    $crate::python::extrapolation(extrapolate, points, slope)?,
+                    options.map(|o| o.0).unwrap_or_default(),
                 )?)))
             }
 
@@ -276,170 +593,1078 @@ macro_rules! impl_estimator {
             ///     Experimental data for liquid density.
             /// temperature : SIArray1
             ///     Temperature for experimental data points.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the phase equilibrium iterations.
             ///
             /// Returns
             /// -------
             /// DataSet
             #[staticmethod]
-            #[pyo3(text_signature = "(target, temperature)")]
+            #[pyo3(text_signature = "(target, temperature, options=None)")]
             fn equilibrium_liquid_density(
                 target: &PySIArray1,
                 temperature: &PySIArray1,
+                options: Option<PyPredictionOptions>,
             ) -> PyResult<Self> {
                 Ok(Self(Rc::new(EquilibriumLiquidDensity::<SIUnit>::new(
                     target.clone().into(),
                     temperature.clone().into(),
+                    options.map(|o| o.0).unwrap_or_default(),
                 )?)))
             }
 
-            /// Return `input` as ``Dict[str, SIArray1]``.
-            #[getter]
-            fn get_input(&self) -> HashMap<String, PySIArray1> {
-                let mut m = HashMap::with_capacity(2);
-                self.0.get_input().drain().for_each(|(k, v)| {
-                    m.insert(k, PySIArray1::from(v));
-                });
-                m
-            }
-
-            /// Return `target` as ``SIArray1``.
-            #[getter]
-            fn get_target(&self) -> PySIArray1 {
-                PySIArray1::from(self.0.target())
-            }
-
-            /// Return `target` as ``SIArray1``.
-            #[getter]
-            fn get_datapoints(&self) -> usize {
-                self.0.datapoints()
-            }
-
-            fn __repr__(&self) -> PyResult<String> {
-                Ok(self.0.to_string())
-            }
-        }
-
-        /// A collection `DataSets` that can be used to compute metrics for experimental data.
-        ///
-        /// Parameters
-        /// ----------
-        /// data : List[DataSet]
-        ///     The properties and experimental data points to add to
-        ///     the estimator.
-        /// weights : List[float]
-        ///     The weight of each property. When computing the cost function,
-        ///     the weights are normalized (sum of weights equals unity).
-        /// losses : List[Loss]
-        ///     The loss functions for each property.
-        ///
-        /// Returns
-        /// -------
-        /// Estimator
-        #[pyclass(name = "Estimator", unsendable)]
-        #[pyo3(text_signature = "(data, weights, losses)")]
-        pub struct PyEstimator(Estimator<SIUnit, $eos>);
-
-        #[pymethods]
-        impl PyEstimator {
-            #[new]
-            fn new(data: Vec<PyDataSet>, weights: Vec<f64>, losses: Vec<PyLoss>) -> Self {
-                Self(Estimator::new(
-                    data.iter().map(|d| d.0.clone()).collect(),
-                    weights,
-                    losses.iter().map(|l| l.0.clone()).collect(),
-                ))
-            }
-
-            /// Compute the cost function for each ``DataSet``.
-            ///
-            /// The cost function is:
-            /// - The relative difference between prediction and target value,
-            /// - to which a loss function is applied,
-            /// - and which is weighted according to the number of datapoints,
-            /// - and the relative weights as defined in the Estimator object.
+            /// Create a DataSet with experimental data for activity
+            /// coefficients at infinite dilution.
             ///
             /// Parameters
             /// ----------
-            /// eos : PyEos
-            ///     The equation of state that is used.
+            /// target : numpy.ndarray[Float]
+            ///     Experimental data for the activity coefficient of the
+            ///     solute at infinite dilution.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// pressure : SIArray1
+            ///     Pressure for experimental data points.
+            /// solute : int
+            ///     Index of the solute in the equation of state.
+            /// solvent : int
+            ///     Index of the solvent in the equation of state. Has to be
+            ///     different from the index of the solute.
             ///
             /// Returns
             /// -------
-            /// numpy.ndarray[Float]
-            ///     The cost function evaluated for each experimental data point
-            ///     of each ``DataSet``.
-            #[pyo3(text_signature = "($self, eos)")]
-            fn cost<'py>(&self, eos: &$py_eos, py: Python<'py>) -> PyResult<&'py PyArray1<f64>> {
-                Ok(self.0.cost(&eos.0)?.view().to_pyarray(py))
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature, pressure, solute, solvent)")]
+            fn infinite_dilution_activity_coefficient(
+                target: &PyArray1<f64>,
+                temperature: &PySIArray1,
+                pressure: &PySIArray1,
+                solute: usize,
+                solvent: usize,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(
+                    InfiniteDilutionActivityCoefficient::<SIUnit>::new(
+                        target.to_owned_array().into(),
+                        temperature.clone().into(),
+                        pressure.clone().into(),
+                        solute,
+                        solvent,
+                    )?,
+                )))
             }
 
-            /// Return the properties as computed by the
-            /// equation of state for each `DataSet`.
+            /// Create a DataSet with experimental data for Henry's law constants.
+            ///
+            /// The cost function is the difference of the logarithms of
+            /// predicted and experimental Henry's law constants.
             ///
             /// Parameters
             /// ----------
-            /// eos : PyEos
-            ///     The equation of state that is used.
+            /// target : SIArray1
+            ///     Experimental data for Henry's law constants.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// solute : int
+            ///     Index of the solute in the equation of state.
+            /// solvent : int
+            ///     Index of the solvent in the equation of state.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the phase equilibrium iterations of the solvent.
             ///
             /// Returns
             /// -------
-            /// List[SIArray1]
-            #[pyo3(text_signature = "($self, eos)")]
-            fn predict(&self, eos: &$py_eos) -> PyResult<Vec<PySIArray1>> {
-                Ok(self
-                    .0
-                    .predict(&eos.0)?
-                    .iter()
-                    .map(|d| PySIArray1::from(d.clone()))
-                    .collect())
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature, solute, solvent, options=None)")]
+            fn henry_constant(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                solute: usize,
+                solvent: usize,
+                options: Option<PyPredictionOptions>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(HenryConstant::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    solute,
+                    solvent,
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?)))
             }
 
-            /// Return the relative difference between experimental data
-            /// and prediction of the equation of state for each ``DataSet``.
-            ///
-            /// The relative difference is computed as:
+            /// Create a DataSet with experimental azeotropic points
+            /// of a binary mixture.
             ///
-            /// .. math:: \text{Relative Difference} = \frac{x_i^\text{prediction} - x_i^\text{experiment}}{x_i^\text{experiment}}
+            /// The cost function contains the relative difference of the
+            /// azeotropic pressure or temperature, followed by the difference
+            /// of the azeotropic mole fractions. If the model does not
+            /// predict an azeotrope, the cost function is NaN.
             ///
             /// Parameters
             /// ----------
-            /// eos : PyEos
-            ///     The equation of state that is used.
+            /// target : SIArray1
+            ///     Experimental azeotropic pressures (for isothermal data)
+            ///     or temperatures (for isobaric data).
+            /// temperature_or_pressure : SIArray1
+            ///     Temperature or pressure for experimental data points.
+            /// molefracs : numpy.ndarray[Float]
+            ///     Azeotropic mole fractions of the first component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the bubble point iterations.
             ///
             /// Returns
             /// -------
-            /// List[numpy.ndarray[Float]]
-            #[pyo3(text_signature = "($self, eos)")]
-            fn relative_difference<'py>(
-                &self,
-                eos: &$py_eos,
-                py: Python<'py>,
-            ) -> PyResult<Vec<&'py PyArray1<f64>>> {
-                Ok(self
-                    .0
-                    .relative_difference(&eos.0)?
-                    .iter()
-                    .map(|d| d.view().to_pyarray(py))
-                    .collect())
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature_or_pressure, molefracs, options=None)")]
+            fn azeotrope(
+                target: &PySIArray1,
+                temperature_or_pressure: &PySIArray1,
+                molefracs: &PyArray1<f64>,
+                options: Option<PyPredictionOptions>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(Azeotrope::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature_or_pressure.clone().into(),
+                    molefracs.to_owned_array(),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?)))
             }
 
-            /// Return the mean absolute relative difference for each ``DataSet``.
-            ///
-            /// The mean absolute relative difference is computed as:
-            ///
-            /// .. math:: \text{MARD} = \frac{1}{N}\sum_{i=1}^{N} \left|\frac{x_i^\text{prediction} - x_i^\text{experiment}}{x_i^\text{experiment}} \right|
+            /// Create a DataSet with experimental bubble point pressures
+            /// of a binary mixture.
             ///
             /// Parameters
             /// ----------
-            /// eos : PyEos
-            ///     The equation of state that is used.
+            /// target : SIArray1
+            ///     Experimental bubble point pressures.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// liquid_molefracs : numpy.ndarray[Float]
+            ///     Liquid mole fractions of the first component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the bubble point iterations.
+            /// cost_function : str, optional
+            ///     The cost function: 'relative_difference' (default) or 'distance'.
             ///
             /// Returns
             /// -------
-            /// numpy.ndarray[Float]
-            #[pyo3(text_signature = "($self, eos)")]
-            fn mean_absolute_relative_difference<'py>(
-                &self,
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, temperature, liquid_molefracs, options=None, cost_function=None)"
+            )]
+            fn binary_tpx(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                liquid_molefracs: &PyArray1<f64>,
+                options: Option<PyPredictionOptions>,
+                cost_function: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(BinaryTPx::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    liquid_molefracs.to_owned_array(),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?
+                .cost_function($crate::python::binary_vle_cost(cost_function)?)?)))
+            }
+
+            /// Create a DataSet with experimental dew point pressures
+            /// of a binary mixture.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental dew point pressures.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// vapor_molefracs : numpy.ndarray[Float]
+            ///     Vapor mole fractions of the first component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the dew point iterations.
+            /// cost_function : str, optional
+            ///     The cost function: 'relative_difference' (default) or 'distance'.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, temperature, vapor_molefracs, options=None, cost_function=None)"
+            )]
+            fn binary_tpy(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                vapor_molefracs: &PyArray1<f64>,
+                options: Option<PyPredictionOptions>,
+                cost_function: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(BinaryTPy::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    vapor_molefracs.to_owned_array(),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?
+                .cost_function($crate::python::binary_vle_cost(cost_function)?)?)))
+            }
+
+            /// Create a DataSet with experimental pressures
+            /// of a binary mixture with known compositions of both phases.
+            ///
+            /// The cost function contains the relative differences of
+            /// the bubble point pressures, followed by those of the
+            /// dew point pressures.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental pressures.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// liquid_molefracs : numpy.ndarray[Float]
+            ///     Liquid mole fractions of the first component.
+            /// vapor_molefracs : numpy.ndarray[Float]
+            ///     Vapor mole fractions of the first component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the bubble and dew point iterations.
+            /// cost_function : str, optional
+            ///     The cost function: 'relative_difference' (default) or 'chemical_potential'.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, temperature, liquid_molefracs, vapor_molefracs, options=None, cost_function=None)"
+            )]
+            fn binary_tpxy(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                liquid_molefracs: &PyArray1<f64>,
+                vapor_molefracs: &PyArray1<f64>,
+                options: Option<PyPredictionOptions>,
+                cost_function: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(BinaryTPxy::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    liquid_molefracs.to_owned_array(),
+                    vapor_molefracs.to_owned_array(),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?
+                .cost_function($crate::python::binary_vle_cost(cost_function)?)?)))
+            }
+
+            /// Create a DataSet with experimental bubble point temperatures
+            /// of a binary mixture.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental bubble point temperatures.
+            /// pressure : SIArray1
+            ///     Pressure for experimental data points.
+            /// liquid_molefracs : numpy.ndarray[Float]
+            ///     Liquid mole fractions of the first component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the bubble point iterations.
+            /// cost_function : str, optional
+            ///     The cost function: 'relative_difference' (default) or 'distance'.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, pressure, liquid_molefracs, options=None, cost_function=None)"
+            )]
+            fn binary_ptx(
+                target: &PySIArray1,
+                pressure: &PySIArray1,
+                liquid_molefracs: &PyArray1<f64>,
+                options: Option<PyPredictionOptions>,
+                cost_function: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(BinaryPTx::<SIUnit>::new(
+                    target.clone().into(),
+                    pressure.clone().into(),
+                    liquid_molefracs.to_owned_array(),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?
+                .cost_function($crate::python::binary_vle_cost(cost_function)?)?)))
+            }
+
+            /// Create a DataSet with experimental dew point temperatures
+            /// of a binary mixture.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental dew point temperatures.
+            /// pressure : SIArray1
+            ///     Pressure for experimental data points.
+            /// vapor_molefracs : numpy.ndarray[Float]
+            ///     Vapor mole fractions of the first component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the dew point iterations.
+            /// cost_function : str, optional
+            ///     The cost function: 'relative_difference' (default) or 'distance'.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, pressure, vapor_molefracs, options=None, cost_function=None)"
+            )]
+            fn binary_pty(
+                target: &PySIArray1,
+                pressure: &PySIArray1,
+                vapor_molefracs: &PyArray1<f64>,
+                options: Option<PyPredictionOptions>,
+                cost_function: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(BinaryPTy::<SIUnit>::new(
+                    target.clone().into(),
+                    pressure.clone().into(),
+                    vapor_molefracs.to_owned_array(),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?
+                .cost_function($crate::python::binary_vle_cost(cost_function)?)?)))
+            }
+
+            /// Create a DataSet with experimental temperatures
+            /// of a binary mixture with known compositions of both phases.
+            ///
+            /// The cost function contains the relative differences of
+            /// the bubble point temperatures, followed by those of the
+            /// dew point temperatures.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental temperatures.
+            /// pressure : SIArray1
+            ///     Pressure for experimental data points.
+            /// liquid_molefracs : numpy.ndarray[Float]
+            ///     Liquid mole fractions of the first component.
+            /// vapor_molefracs : numpy.ndarray[Float]
+            ///     Vapor mole fractions of the first component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the bubble and dew point iterations.
+            /// cost_function : str, optional
+            ///     The cost function: 'relative_difference' (default) or 'chemical_potential'.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, pressure, liquid_molefracs, vapor_molefracs, options=None, cost_function=None)"
+            )]
+            fn binary_ptxy(
+                target: &PySIArray1,
+                pressure: &PySIArray1,
+                liquid_molefracs: &PyArray1<f64>,
+                vapor_molefracs: &PyArray1<f64>,
+                options: Option<PyPredictionOptions>,
+                cost_function: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(BinaryPTxy::<SIUnit>::new(
+                    target.clone().into(),
+                    pressure.clone().into(),
+                    liquid_molefracs.to_owned_array(),
+                    vapor_molefracs.to_owned_array(),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?
+                .cost_function($crate::python::binary_vle_cost(cost_function)?)?)))
+            }
+
+            /// Create a DataSet with experimental VLE data of a mixture
+            /// with an arbitrary number of components.
+            ///
+            /// The cost function contains the relative differences of the
+            /// bubble point pressures (for isothermal data) or temperatures
+            /// (for isobaric data). If vapor mole fractions are provided,
+            /// the differences of the vapor mole fractions are appended.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental bubble point pressures or temperatures.
+            /// temperature_or_pressure : SIArray1
+            ///     Temperature or pressure for experimental data points.
+            /// liquid_molefracs : numpy.ndarray[Float]
+            ///     Liquid mole fractions with one row per data point
+            ///     and one column per component.
+            /// vapor_molefracs : numpy.ndarray[Float], optional
+            ///     Vapor mole fractions with one row per data point
+            ///     and one column per component.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the bubble point iterations.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, temperature_or_pressure, liquid_molefracs, vapor_molefracs=None, options=None)"
+            )]
+            fn multicomponent_vle(
+                target: &PySIArray1,
+                temperature_or_pressure: &PySIArray1,
+                liquid_molefracs: &numpy::PyArray2<f64>,
+                vapor_molefracs: Option<&numpy::PyArray2<f64>>,
+                options: Option<PyPredictionOptions>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(MulticomponentVLE::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature_or_pressure.clone().into(),
+                    liquid_molefracs.to_owned_array(),
+                    vapor_molefracs.map(|y| y.to_owned_array()),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?)))
+            }
+
+            /// Create a DataSet with experimental data for Joule-Thomson coefficients.
+            ///
+            /// The cost function is the difference between prediction and
+            /// experimental data divided by the mean absolute value of
+            /// the experimental data.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental data for Joule-Thomson coefficients.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// pressure : SIArray1
+            ///     Pressure for experimental data points.
+            /// density_initialization : str, optional
+            ///     Phase of the states. 'vapor' or 'liquid'.
+            ///     If not given, the most stable phase is used.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature, pressure, density_initialization=None)")]
+            fn joule_thomson_coefficient(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                pressure: &PySIArray1,
+                density_initialization: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(JouleThomsonCoefficient::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    pressure.clone().into(),
+                    $crate::python::density_initialization(density_initialization)?,
+                )?)))
+            }
+
+            /// Create a DataSet with experimental data for isothermal compressibilities.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental data for isothermal compressibilities.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// pressure : SIArray1
+            ///     Pressure for experimental data points.
+            /// density_initialization : str, optional
+            ///     Phase of the states. 'vapor' or 'liquid'.
+            ///     If not given, the most stable phase is used.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature, pressure, density_initialization=None)")]
+            fn isothermal_compressibility(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                pressure: &PySIArray1,
+                density_initialization: Option<&str>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(IsothermalCompressibility::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    pressure.clone().into(),
+                    $crate::python::density_initialization(density_initialization)?,
+                )?)))
+            }
+
+            /// Create a DataSet with experimental data for a property of a state.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental data for the property.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// pressure : SIArray1
+            ///     Pressure for experimental data points.
+            /// property : str
+            ///     Name of the property. One of 'density', 'mass_density',
+            ///     'compressibility_factor', 'molar_isochoric_heat_capacity',
+            ///     'molar_isobaric_heat_capacity', 'isothermal_compressibility',
+            ///     'isentropic_compressibility', 'joule_thomson_coefficient'
+            ///     or 'speed_of_sound'.
+            /// density_initialization : str, optional
+            ///     Initialization of the density iteration. 'vapor' or 'liquid'.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(
+                text_signature = "(target, temperature, pressure, property, density_initialization=None)"
+            )]
+            fn state_property(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                pressure: &PySIArray1,
+                property: &str,
+                density_initialization: Option<&str>,
+            ) -> PyResult<Self> {
+                let density_initialization =
+                    $crate::python::density_initialization(density_initialization)?;
+                Ok(Self(Rc::new(StatePropertyDataSet::<SIUnit, $eos>::from_property_name(
+                    target.clone().into(),
+                    property,
+                    temperature.clone().into(),
+                    pressure.clone().into(),
+                    density_initialization,
+                )?)))
+            }
+
+            /// Return statistics of the relative difference between
+            /// experimental data and prediction of the equation of state.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            /// tolerance : float
+            ///     The absolute relative difference up to which a data
+            ///     point is considered as well described.
+            ///
+            /// Returns
+            /// -------
+            /// DeviationStatistics
+            #[pyo3(text_signature = "($self, eos, tolerance)")]
+            fn statistics(&self, eos: &$py_eos, tolerance: f64) -> PyResult<PyDeviationStatistics> {
+                Ok(PyDeviationStatistics(self.0.statistics(&eos.0, tolerance)?))
+            }
+
+            /// Return a new DataSet that only contains the data points
+            /// with the given indices.
+            ///
+            /// Parameters
+            /// ----------
+            /// indices : List[int]
+            ///     Indices of the data points. Indices can be repeated.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[pyo3(text_signature = "($self, indices)")]
+            fn subset(&self, indices: Vec<usize>) -> PyResult<Self> {
+                Ok(Self(self.0.subset(&indices)?))
+            }
+
+            /// Return a new DataSet that only contains the data points
+            /// for which an input quantity is within the given bounds.
+            ///
+            /// Parameters
+            /// ----------
+            /// input : str
+            ///     Name of the input quantity, e.g. 'temperature'.
+            /// lower : SINumber
+            ///     Lower bound (inclusive).
+            /// upper : SINumber
+            ///     Upper bound (inclusive).
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[pyo3(text_signature = "($self, input, lower, upper)")]
+            fn filter_range(
+                &self,
+                input: &str,
+                lower: quantity::python::PySINumber,
+                upper: quantity::python::PySINumber,
+            ) -> PyResult<Self> {
+                Ok(Self(self.0.filter_range(input, lower.into(), upper.into())?))
+            }
+
+            /// Return `input` as ``Dict[str, SIArray1]``.
+            #[getter]
+            fn get_input(&self) -> HashMap<String, PySIArray1> {
+                let mut m = HashMap::with_capacity(2);
+                self.0.get_input().drain().for_each(|(k, v)| {
+                    m.insert(k, PySIArray1::from(v));
+                });
+                m
+            }
+
+            /// Return `target` as ``SIArray1``.
+            #[getter]
+            fn get_target(&self) -> PySIArray1 {
+                PySIArray1::from(self.0.target())
+            }
+
+            /// Return `target` as ``SIArray1``.
+            #[getter]
+            fn get_datapoints(&self) -> usize {
+                self.0.datapoints()
+            }
+
+            fn __repr__(&self) -> PyResult<String> {
+                Ok(self.0.to_string())
+            }
+        }
+
+        /// Base class for data sets that are implemented in Python.
+        ///
+        /// Subclasses have to implement the methods `target`, `predict`
+        /// and `get_input`. Optionally, `target_str` and `input_str`
+        /// can be overwritten to describe the data set, and `subset`
+        /// can be implemented to enable resampling and cross validation.
+        ///
+        /// Instances of subclasses can be used in an ``Estimator``
+        /// together with the built-in ``DataSet``s. The experimental
+        /// data, the input and the descriptions are read once when the
+        /// instance is passed to the ``Estimator``; later changes of the
+        /// instance are not seen by the ``Estimator``. `predict` is
+        /// called for every evaluation.
+        #[pyclass(name = "CustomDataSet", subclass, unsendable)]
+        #[pyo3(text_signature = "()")]
+        pub struct PyCustomDataSet;
+
+        #[pymethods]
+        impl PyCustomDataSet {
+            #[new]
+            fn new() -> Self {
+                Self
+            }
+
+            /// Return the experimental data.
+            ///
+            /// Returns
+            /// -------
+            /// SIArray1
+            fn target(&self) -> PyResult<PySIArray1> {
+                Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                    "`target` has to be implemented by the subclass.",
+                ))
+            }
+
+            /// Compute the target property using an equation of state.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            ///
+            /// Returns
+            /// -------
+            /// SIArray1
+            #[pyo3(text_signature = "($self, eos)")]
+            #[allow(unused_variables)]
+            fn predict(&self, eos: &$py_eos) -> PyResult<PySIArray1> {
+                Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                    "`predict` has to be implemented by the subclass.",
+                ))
+            }
+
+            /// Return the input quantities.
+            ///
+            /// Returns
+            /// -------
+            /// Dict[str, SIArray1]
+            fn get_input(&self) -> PyResult<HashMap<String, PySIArray1>> {
+                Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                    "`get_input` has to be implemented by the subclass.",
+                ))
+            }
+
+            /// Return the description of the target property.
+            ///
+            /// Returns
+            /// -------
+            /// str
+            fn target_str(&self) -> String {
+                "custom property".to_owned()
+            }
+
+            /// Return the descriptions of the input quantities.
+            ///
+            /// Returns
+            /// -------
+            /// List[str]
+            fn input_str(slf: &PyCell<Self>) -> PyResult<Vec<String>> {
+                let input = slf.call_method0("get_input")?;
+                input.downcast::<pyo3::types::PyDict>()?.keys().extract()
+            }
+
+            /// Return a new data set that only contains the data points
+            /// with the given indices. Indices can be repeated.
+            ///
+            /// Parameters
+            /// ----------
+            /// indices : List[int]
+            ///     Indices of the selected data points.
+            ///
+            /// Returns
+            /// -------
+            /// CustomDataSet
+            #[pyo3(text_signature = "($self, indices)")]
+            #[allow(unused_variables)]
+            fn subset(&self, indices: Vec<usize>) -> PyResult<PyObject> {
+                Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                    "`subset` is not implemented by the subclass.",
+                ))
+            }
+        }
+
+        /// Adapter that makes a subclass of `CustomDataSet` usable as [DataSet].
+        ///
+        /// Experimental data, input and descriptions are a snapshot that is
+        /// read once from the Python object, predictions are evaluated in
+        /// Python. Subsets are created by the `subset` method of the Python
+        /// object, if the subclass implements it.
+        struct PyDataSetAdapter {
+            dataset: PyObject,
+            target: quantity::QuantityArray1<SIUnit>,
+            target_str: String,
+            input_str: Vec<String>,
+            input: HashMap<String, quantity::QuantityArray1<SIUnit>>,
+        }
+
+        impl PyDataSetAdapter {
+            fn new(dataset: &PyAny) -> PyResult<Self> {
+                let target: PySIArray1 = dataset.call_method0("target")?.extract()?;
+                let target: quantity::QuantityArray1<SIUnit> = target.into();
+                let input: HashMap<String, PySIArray1> =
+                    dataset.call_method0("get_input")?.extract()?;
+                let input: HashMap<_, quantity::QuantityArray1<SIUnit>> =
+                    input.into_iter().map(|(k, v)| (k, v.into())).collect();
+                if input.values().any(|v| v.len() != target.len()) {
+                    return Err(EstimatorError::IncompatibleInput.into());
+                }
+                Ok(Self {
+                    dataset: dataset.into(),
+                    target,
+                    target_str: dataset.call_method0("target_str")?.extract()?,
+                    input_str: dataset.call_method0("input_str")?.extract()?,
+                    input,
+                })
+            }
+        }
+
+        impl DataSet<SIUnit, $eos> for PyDataSetAdapter {
+            fn target(&self) -> quantity::QuantityArray1<SIUnit> {
+                self.target.clone()
+            }
+
+            fn target_str(&self) -> &str {
+                &self.target_str
+            }
+
+            fn input_str(&self) -> Vec<&str> {
+                self.input_str.iter().map(|s| s.as_str()).collect()
+            }
+
+            fn predict(&self, eos: &Rc<$eos>) -> Result<quantity::QuantityArray1<SIUnit>, EstimatorError> {
+                type EosClass = $py_eos;
+                Python::with_gil(|py| {
+                    let eos = Py::new(py, EosClass { 0: eos.clone() })?;
+                    let prediction: PySIArray1 = self
+                        .dataset
+                        .call_method1(py, "predict", (eos,))?
+                        .extract(py)?;
+                    let prediction: quantity::QuantityArray1<SIUnit> = prediction.into();
+                    if prediction.len() != self.target.len() {
+                        return Err(EstimatorError::IncompatibleInput);
+                    }
+                    Ok(prediction)
+                })
+            }
+
+            fn cost(&self, eos: &Rc<$eos>, loss: Loss) -> Result<ndarray::Array1<f64>, EstimatorError> {
+                let mut cost = self.relative_difference(eos)?;
+                loss.apply(&mut cost.view_mut());
+                Ok(cost / self.datapoints() as f64)
+            }
+
+            fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<SIUnit, $eos>>, EstimatorError> {
+                Python::with_gil(|py| {
+                    match self.dataset.call_method1(py, "subset", (indices.to_vec(),)) {
+                        Ok(subset) => Ok(Rc::new(PyDataSetAdapter::new(subset.as_ref(py))?) as Rc<dyn DataSet<SIUnit, $eos>>),
+                        Err(e) if e.is_instance_of::<pyo3::exceptions::PyNotImplementedError>(py) => {
+                            Err(EstimatorError::SubsetNotSupported(self.target_str.clone()))
+                        }
+                        Err(e) => Err(e.into()),
+                    }
+                })
+            }
+
+            fn get_input(&self) -> HashMap<String, quantity::QuantityArray1<SIUnit>> {
+                self.input.clone()
+            }
+        }
+
+        impl PyDataSet {
+            /// Convert a `DataSet` or an instance of a subclass of
+            /// `CustomDataSet` into a `DataSet`.
+            fn from_any(dataset: &PyAny) -> PyResult<Self> {
+                if let Ok(dataset) = dataset.extract::<Self>() {
+                    Ok(dataset)
+                } else if dataset.is_instance_of::<PyCustomDataSet>()? {
+                    Ok(Self(Rc::new(PyDataSetAdapter::new(dataset)?)))
+                } else {
+                    Err(pyo3::exceptions::PyTypeError::new_err(
+                        "Expected a `DataSet` or a subclass of `CustomDataSet`.",
+                    ))
+                }
+            }
+        }
+
+        /// A collection `DataSets` that can be used to compute metrics for experimental data.
+        ///
+        /// Parameters
+        /// ----------
+        /// data : List[Union[DataSet, CustomDataSet]]
+        ///     The properties and experimental data points to add to
+        ///     the estimator. Instances of subclasses of ``CustomDataSet``
+        ///     can be mixed with the built-in ``DataSet``s.
+        /// weights : List[float]
+        ///     The weight of each property. When computing the cost function,
+        ///     the weights are normalized (sum of weights equals unity).
+        /// losses : List[Loss]
+        ///     The loss functions for each property.
+        ///
+        /// Returns
+        /// -------
+        /// Estimator
+        #[pyclass(name = "Estimator", unsendable)]
+        #[pyo3(text_signature = "(data, weights, losses)")]
+        pub struct PyEstimator(Estimator<SIUnit, $eos>);
+
+        #[pymethods]
+        impl PyEstimator {
+            #[new]
+            fn new(data: Vec<&PyAny>, weights: Vec<f64>, losses: Vec<PyLoss>) -> PyResult<Self> {
+                Ok(Self(Estimator::new(
+                    data.into_iter()
+                        .map(|d| Ok(PyDataSet::from_any(d)?.0))
+                        .collect::<PyResult<_>>()?,
+                    weights,
+                    losses.iter().map(|l| l.0.clone()).collect(),
+                )))
+            }
+
+            /// Compute the cost function for each ``DataSet``.
+            ///
+            /// The cost function is:
+            /// - The relative difference between prediction and target value,
+            /// - to which a loss function is applied,
+            /// - and which is weighted according to the number of datapoints,
+            /// - and the relative weights as defined in the Estimator object.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            ///
+            /// Returns
+            /// -------
+            /// numpy.ndarray[Float]
+            ///     The cost function evaluated for each experimental data point
+            ///     of each ``DataSet``.
+            #[pyo3(text_signature = "($self, eos)")]
+            fn cost<'py>(&self, eos: &$py_eos, py: Python<'py>) -> PyResult<&'py PyArray1<f64>> {
+                Ok(self.0.cost(&eos.0)?.view().to_pyarray(py))
+            }
+
+            /// Return the properties as computed by the
+            /// equation of state for each `DataSet`.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            ///
+            /// Returns
+            /// -------
+            /// List[SIArray1]
+            #[pyo3(text_signature = "($self, eos)")]
+            fn predict(&self, eos: &$py_eos) -> PyResult<Vec<PySIArray1>> {
+                Ok(self
+                    .0
+                    .predict(&eos.0)?
+                    .iter()
+                    .map(|d| PySIArray1::from(d.clone()))
+                    .collect())
+            }
+
+            /// Return the properties as computed by the equation of state
+            /// together with the status of every data point for each ``DataSet``.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            ///
+            /// Returns
+            /// -------
+            /// List[(SIArray1, List[str])]
+            ///     The predictions and the status of every data point:
+            ///     'converged', 'extrapolated' or 'failed: <reason>'.
+            #[pyo3(text_signature = "($self, eos)")]
+            fn predict_with_status(&self, eos: &$py_eos) -> PyResult<Vec<(PySIArray1, Vec<String>)>> {
+                Ok(self
+                    .0
+                    .predict_with_status(&eos.0)?
+                    .into_iter()
+                    .map(|p| {
+                        (
+                            p.values.into(),
+                            p.status.iter().map(|s| s.to_string()).collect(),
+                        )
+                    })
+                    .collect())
+            }
+
+            /// Set the treatment of data points for which the cost function
+            /// is not finite, e.g., because the equation of state failed.
+            ///
+            /// Parameters
+            /// ----------
+            /// policy : str
+            ///     'propagate' (keep NaN), 'skip' (set the cost to zero),
+            ///     'penalize' (use `penalty` as residual) or 'error'.
+            /// penalty : float, optional
+            ///     The residual of failed data points for policy 'penalize'.
+            /// index : int, optional
+            ///     The index of the ``DataSet``. If not given, the policy
+            ///     is used for all ``DataSet``s.
+            #[pyo3(text_signature = "($self, policy, penalty=None, index=None)")]
+            fn set_failure_policy(
+                &mut self,
+                policy: &str,
+                penalty: Option<f64>,
+                index: Option<usize>,
+            ) -> PyResult<()> {
+                let policy = match (policy, penalty) {
+                    ("propagate", _) => FailurePolicy::Propagate,
+                    ("skip", _) => FailurePolicy::Skip,
+                    ("penalize", Some(penalty)) => FailurePolicy::Penalize(penalty),
+                    ("penalize", None) => {
+                        return Err(pyo3::exceptions::PyValueError::new_err(
+                            "The policy 'penalize' requires a `penalty`.",
+                        ))
+                    }
+                    ("error", _) => FailurePolicy::Error,
+                    _ => {
+                        return Err(pyo3::exceptions::PyValueError::new_err(
+                            "`policy` must be 'propagate', 'skip', 'penalize' or 'error'.",
+                        ))
+                    }
+                };
+                Ok(self.0.set_failure_policy(policy, index)?)
+            }
+
+            /// Set a finite penalty for all data points that remain
+            /// non-finite after the failure policies are applied.
+            ///
+            /// During an optimization, the penalty also replaces
+            /// the cost if the model can not be evaluated at all.
+            ///
+            /// Parameters
+            /// ----------
+            /// residual : float, optional
+            ///     The residual of failed data points. If not given,
+            ///     the penalty is removed.
+            /// slope : float, optional
+            ///     The increase of the residual with the relative distance
+            ///     of the parameters from `reference`.
+            /// reference : numpy.ndarray[Float], optional
+            ///     The reference parameters, e.g., the initial parameters.
+            #[pyo3(text_signature = "($self, residual=None, slope=None, reference=None)")]
+            fn set_failure_penalty(
+                &mut self,
+                residual: Option<f64>,
+                slope: Option<f64>,
+                reference: Option<&PyArray1<f64>>,
+            ) -> PyResult<()> {
+                let penalty = match (residual, slope, reference) {
+                    (None, _, _) => None,
+                    (Some(residual), None, None) => Some(FailurePenalty::new(residual)),
+                    (Some(residual), Some(slope), Some(reference)) => Some(
+                        FailurePenalty::new(residual).distance(reference.to_owned_array(), slope),
+                    ),
+                    _ => {
+                        return Err(pyo3::exceptions::PyValueError::new_err(
+                            "`slope` and `reference` have to be given together.",
+                        ))
+                    }
+                };
+                self.0.set_failure_penalty(penalty);
+                Ok(())
+            }
+
+            /// Return the relative difference between experimental data
+            /// and prediction of the equation of state for each ``DataSet``.
+            ///
+            /// The relative difference is computed as:
+            ///
+            /// .. math:: \text{Relative Difference} = \frac{x_i^\text{prediction} - x_i^\text{experiment}}{x_i^\text{experiment}}
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            ///
+            /// Returns
+            /// -------
+            /// List[numpy.ndarray[Float]]
+            #[pyo3(text_signature = "($self, eos)")]
+            fn relative_difference<'py>(
+                &self,
+                eos: &$py_eos,
+                py: Python<'py>,
+            ) -> PyResult<Vec<&'py PyArray1<f64>>> {
+                Ok(self
+                    .0
+                    .relative_difference(&eos.0)?
+                    .iter()
+                    .map(|d| d.view().to_pyarray(py))
+                    .collect())
+            }
+
+            /// Return the mean absolute relative difference for each ``DataSet``.
+            ///
+            /// The mean absolute relative difference is computed as:
+            ///
+            /// .. math:: \text{MARD} = \frac{1}{N}\sum_{i=1}^{N} \left|\frac{x_i^\text{prediction} - x_i^\text{experiment}}{x_i^\text{experiment}} \right|
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            ///
+            /// Returns
+            /// -------
+            /// numpy.ndarray[Float]
+            #[pyo3(text_signature = "($self, eos)")]
+            fn mean_absolute_relative_difference<'py>(
+                &self,
                 eos: &$py_eos,
                 py: Python<'py>,
             ) -> PyResult<&'py PyArray1<f64>> {
@@ -450,6 +1675,35 @@ macro_rules! impl_estimator {
                     .to_pyarray(py))
             }
 
+            /// Return statistics of the relative difference between
+            /// experimental data and prediction of the equation of state
+            /// for each ``DataSet``.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            /// tolerance : float
+            ///     The absolute relative difference up to which a data
+            ///     point is considered as well described.
+            ///
+            /// Returns
+            /// -------
+            /// List[DeviationStatistics]
+            #[pyo3(text_signature = "($self, eos, tolerance)")]
+            fn statistics(
+                &self,
+                eos: &$py_eos,
+                tolerance: f64,
+            ) -> PyResult<Vec<PyDeviationStatistics>> {
+                Ok(self
+                    .0
+                    .statistics(&eos.0, tolerance)?
+                    .into_iter()
+                    .map(PyDeviationStatistics)
+                    .collect())
+            }
+
             /// Return the stored ``DataSet``s.
             ///
             /// Returns
@@ -489,23 +1743,30 @@ macro_rules! impl_estimator_entropy_scaling {
             ///     Experimental data for viscosity.
             /// temperature : SIArray1
             ///     Temperature for experimental data points.
-            /// pressure : SIArray1
-            ///     Pressure for experimental data points.
+            /// pressure_or_density : SIArray1
+            ///     Pressure or molar density for experimental data points.
+            /// density_initialization : str, optional
+            ///     Phase of the states for pressure input. 'vapor' or 'liquid'.
+            ///     If not given, the most stable phase is used.
             ///
             /// Returns
             /// -------
             /// DataSet
             #[staticmethod]
-            #[pyo3(text_signature = "(target, temperature, pressure)")]
+            #[pyo3(
+                text_signature = "(target, temperature, pressure_or_density, density_initialization=None)"
+            )]
             fn viscosity(
                 target: &PySIArray1,
                 temperature: &PySIArray1,
-                pressure: &PySIArray1,
+                pressure_or_density: &PySIArray1,
+                density_initialization: Option<&str>,
             ) -> PyResult<Self> {
                 Ok(Self(Rc::new(Viscosity::<SIUnit>::new(
                     target.clone().into(),
                     temperature.clone().into(),
-                    pressure.clone().into(),
+                    pressure_or_density.clone().into(),
+                    $crate::python::density_initialization(density_initialization)?,
                 )?)))
             }
 
@@ -517,23 +1778,30 @@ macro_rules! impl_estimator_entropy_scaling {
             ///     Experimental data for thermal conductivity.
             /// temperature : SIArray1
             ///     Temperature for experimental data points.
-            /// pressure : SIArray1
-            ///     Pressure for experimental data points.
+            /// pressure_or_density : SIArray1
+            ///     Pressure or molar density for experimental data points.
+            /// density_initialization : str, optional
+            ///     Phase of the states for pressure input. 'vapor' or 'liquid'.
+            ///     If not given, the most stable phase is used.
             ///
             /// Returns
             /// -------
             /// DataSet
             #[staticmethod]
-            #[pyo3(text_signature = "(target, temperature, pressure)")]
+            #[pyo3(
+                text_signature = "(target, temperature, pressure_or_density, density_initialization=None)"
+            )]
             fn thermal_conductivity(
                 target: &PySIArray1,
                 temperature: &PySIArray1,
-                pressure: &PySIArray1,
+                pressure_or_density: &PySIArray1,
+                density_initialization: Option<&str>,
             ) -> PyResult<Self> {
                 Ok(Self(Rc::new(ThermalConductivity::<SIUnit>::new(
                     target.clone().into(),
                     temperature.clone().into(),
-                    pressure.clone().into(),
+                    pressure_or_density.clone().into(),
+                    $crate::python::density_initialization(density_initialization)?,
                 )?)))
             }
 
@@ -545,25 +1813,258 @@ macro_rules! impl_estimator_entropy_scaling {
             ///     Experimental data for diffusion coefficient.
             /// temperature : SIArray1
             ///     Temperature for experimental data points.
-            /// pressure : SIArray1
-            ///     Pressure for experimental data points.
+            /// pressure_or_density : SIArray1
+            ///     Pressure or molar density for experimental data points.
+            /// density_initialization : str, optional
+            ///     Phase of the states for pressure input. 'vapor' or 'liquid'.
+            ///     If not given, the most stable phase is used.
             ///
             /// Returns
             /// -------
             /// DataSet
             #[staticmethod]
-            #[pyo3(text_signature = "(target, temperature, pressure)")]
+            #[pyo3(
+                text_signature = "(target, temperature, pressure_or_density, density_initialization=None)"
+            )]
             fn diffusion(
                 target: &PySIArray1,
                 temperature: &PySIArray1,
-                pressure: &PySIArray1,
+                pressure_or_density: &PySIArray1,
+                density_initialization: Option<&str>,
             ) -> PyResult<Self> {
                 Ok(Self(Rc::new(Diffusion::<SIUnit>::new(
                     target.clone().into(),
                     temperature.clone().into(),
-                    pressure.clone().into(),
+                    pressure_or_density.clone().into(),
+                    $crate::python::density_initialization(density_initialization)?,
+                )?)))
+            }
+
+            /// Create a DataSet with experimental data for viscosity
+            /// at vapor liquid equilibrium.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental data for viscosity.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// liquid : bool, optional
+            ///     Use the saturated liquid (True) or the saturated
+            ///     vapor (False). Defaults to True.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the phase equilibrium iterations.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature, liquid=True, options=None)")]
+            fn equilibrium_viscosity(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                liquid: Option<bool>,
+                options: Option<PyPredictionOptions>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(EquilibriumViscosity::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    liquid.unwrap_or(true),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?)))
+            }
+
+            /// Create a DataSet with experimental data for thermal conductivity
+            /// at vapor liquid equilibrium.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental data for thermal conductivity.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// liquid : bool, optional
+            ///     Use the saturated liquid (True) or the saturated
+            ///     vapor (False). Defaults to True.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the phase equilibrium iterations.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature, liquid=True, options=None)")]
+            fn equilibrium_thermal_conductivity(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                liquid: Option<bool>,
+                options: Option<PyPredictionOptions>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(EquilibriumThermalConductivity::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    liquid.unwrap_or(true),
+                    options.map(|o| o.0).unwrap_or_default(),
+                )?)))
+            }
+
+            /// Create a DataSet with experimental data for diffusion coefficient
+            /// at vapor liquid equilibrium.
+            ///
+            /// Parameters
+            /// ----------
+            /// target : SIArray1
+            ///     Experimental data for diffusion coefficient.
+            /// temperature : SIArray1
+            ///     Temperature for experimental data points.
+            /// liquid : bool, optional
+            ///     Use the saturated liquid (True) or the saturated
+            ///     vapor (False). Defaults to True.
+            /// options : PredictionOptions, optional
+            ///     Solver options of the phase equilibrium iterations.
+            ///
+            /// Returns
+            /// -------
+            /// DataSet
+            #[staticmethod]
+            #[pyo3(text_signature = "(target, temperature, liquid=True, options=None)")]
+            fn equilibrium_diffusion(
+                target: &PySIArray1,
+                temperature: &PySIArray1,
+                liquid: Option<bool>,
+                options: Option<PyPredictionOptions>,
+            ) -> PyResult<Self> {
+                Ok(Self(Rc::new(EquilibriumDiffusion::<SIUnit>::new(
+                    target.clone().into(),
+                    temperature.clone().into(),
+                    liquid.unwrap_or(true),
+                    options.map(|o| o.0).unwrap_or_default(),
                 )?)))
             }
         }
+
+        #[pymethods]
+        impl PyEstimator {
+            /// Fit the parameters of an entropy scaling correlation to a
+            /// transport property data set by linear least squares.
+            ///
+            /// The correlation is a polynomial in the reduced residual
+            /// entropy per segment, ln(Y / Y_ref) = sum_k c_k (s_res / m)^k,
+            /// which is the viscosity correlation of feos for order 3.
+            ///
+            /// Parameters
+            /// ----------
+            /// eos : PyEos
+            ///     The equation of state that is used.
+            /// index : int
+            ///     Index of the transport property ``DataSet``.
+            /// segments : float, optional
+            ///     Segment number m. Defaults to 1.
+            /// order : int, optional
+            ///     Order of the polynomial. Defaults to 3.
+            ///
+            /// Returns
+            /// -------
+            /// numpy.ndarray[Float]
+            ///     The parameters c_k.
+            #[pyo3(text_signature = "($self, eos, index, segments=1.0, order=3)")]
+            fn fit_entropy_scaling<'py>(
+                &self,
+                eos: &$py_eos,
+                index: usize,
+                segments: Option<f64>,
+                order: Option<usize>,
+                py: Python<'py>,
+            ) -> PyResult<&'py PyArray1<f64>> {
+                let data = self.0.datasets();
+                let dataset = data
+                    .get(index)
+                    .ok_or(EstimatorError::InvalidDataSet(index, data.len()))?;
+                let basis = polynomial_basis(segments.unwrap_or(1.0), order.unwrap_or(3));
+                Ok(fit_entropy_scaling(dataset.as_ref(), &eos.0, basis)?
+                    .view()
+                    .to_pyarray(py))
+            }
+        }
     };
 }
+
+#[cfg(test)]
+#[allow(non_local_definitions)]
+mod tests {
+    use crate::test_eos::propane;
+    use crate::*;
+    use feos_core::cubic::PengRobinson;
+    use numpy::{PyArray1, ToPyArray};
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use quantity::python::PySIArray1;
+    use quantity::si::*;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    #[pyclass(unsendable)]
+    struct PyPengRobinson(Rc<PengRobinson>);
+
+    impl_estimator!(PengRobinson, PyPengRobinson);
+
+    const BAD_SUBCLASSES: &str = r#"
+class WrongPrediction(CustomDataSet):
+    def target(self):
+        return target
+
+    def predict(self, eos):
+        return short
+
+    def get_input(self):
+        return {"temperature": target}
+
+
+class WrongInput(CustomDataSet):
+    def target(self):
+        return target
+
+    def predict(self, eos):
+        return target
+
+    def get_input(self):
+        return {"temperature": short}
+"#;
+
+    #[test]
+    fn custom_dataset_lengths() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let eos = PyPengRobinson(propane());
+            let globals = PyDict::new(py);
+            globals
+                .set_item("CustomDataSet", py.get_type::<PyCustomDataSet>())
+                .unwrap();
+            globals
+                .set_item("Estimator", py.get_type::<PyEstimator>())
+                .unwrap();
+            globals.set_item("Loss", py.get_type::<PyLoss>()).unwrap();
+            globals.set_item("eos", Py::new(py, eos).unwrap()).unwrap();
+            let target = PySIArray1::from(ndarray::arr1(&[300.0, 310.0]) * KELVIN);
+            let short = PySIArray1::from(ndarray::arr1(&[300.0]) * KELVIN);
+            globals
+                .set_item("target", Py::new(py, target).unwrap())
+                .unwrap();
+            globals
+                .set_item("short", Py::new(py, short).unwrap())
+                .unwrap();
+            py.run(BAD_SUBCLASSES, Some(globals), None).unwrap();
+
+            let estimator = "Estimator([WrongPrediction()], [1.0], [Loss.linear()])";
+            let estimator = py.eval(estimator, Some(globals), None).unwrap();
+            let error = estimator.call_method1("cost", (globals.get_item("eos"),));
+            let error = error.unwrap_err();
+            assert!(error.is_instance_of::<pyo3::exceptions::PyRuntimeError>(py));
+            assert!(error.to_string().contains("same amount of data"));
+
+            let estimator = "Estimator([WrongInput()], [1.0], [Loss.linear()])";
+            let error = py.eval(estimator, Some(globals), None).unwrap_err();
+            assert!(error.is_instance_of::<pyo3::exceptions::PyRuntimeError>(py));
+        });
+    }
+}
@@ -0,0 +1,346 @@
+//! Resampling methods to estimate the uncertainty of fitted parameters.
+//!
+//! The data of an [`Estimator`] is resampled, the parameters are refitted
+//! using a user supplied fit routine and the distribution of the
+//! parameters is collected in a [`ResamplingResult`].
+use super::{Estimator, EstimatorError};
+use feos_core::{EosUnit, EquationOfState};
+use ndarray::{Array1, Array2, Axis};
+use quantity::QuantityScalar;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Resampling method that produced a [`ResamplingResult`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResamplingMethod {
+    /// bootstrap resampling of the data points
+    Bootstrap,
+    /// leave-one-dataset-out jackknife
+    Jackknife,
+}
+
+/// Parameters obtained from fits to resampled data.
+#[derive(Clone, Debug)]
+pub struct ResamplingResult {
+    /// parameters of all successful fits (one row per fit)
+    pub parameters: Array2<f64>,
+    /// number of fits that returned an error
+    pub failed: usize,
+    /// resampling method
+    pub method: ResamplingMethod,
+}
+
+impl ResamplingResult {
+    fn new(
+        parameters: Vec<Array1<f64>>,
+        failed: usize,
+        method: ResamplingMethod,
+    ) -> Result<Self, EstimatorError> {
+        let n = parameters
+            .first()
+            .ok_or(EstimatorError::AllFitsFailed(failed))?
+            .len();
+        let mut p = Array2::zeros((parameters.len(), n));
+        for (mut row, x) in p.outer_iter_mut().zip(parameters.iter()) {
+            if x.len() != n {
+                return Err(EstimatorError::IncompatibleInput);
+            }
+            row.assign(x);
+        }
+        Ok(Self {
+            parameters: p,
+            failed,
+            method,
+        })
+    }
+
+    /// Returns the mean of the parameters.
+    pub fn mean(&self) -> Array1<f64> {
+        self.parameters.mean_axis(Axis(0)).unwrap()
+    }
+
+    /// Returns the standard deviation of the parameters.
+    ///
+    /// For bootstrap results, this is the sample standard deviation. For
+    /// jackknife results, the square root of the jackknife variance
+    /// (n-1)/n Σ(θᵢ-θ̄)² is returned.
+    pub fn std(&self) -> Array1<f64> {
+        match self.method {
+            ResamplingMethod::Bootstrap => self.parameters.std_axis(Axis(0), 1.0),
+            ResamplingMethod::Jackknife => {
+                let n = self.parameters.nrows() as f64;
+                self.parameters.std_axis(Axis(0), 0.0) * (n - 1.0).sqrt()
+            }
+        }
+    }
+
+    /// Returns the `q`-th percentile (`0 <= q <= 100`) of each parameter.
+    ///
+    /// Values between two samples are interpolated linearly. Jackknife
+    /// samples do not represent the distribution of the parameters,
+    /// therefore, an error is returned for jackknife results.
+    pub fn percentile(&self, q: f64) -> Result<Array1<f64>, EstimatorError> {
+        if self.method == ResamplingMethod::Jackknife {
+            return Err(EstimatorError::JackknifePercentile);
+        }
+        let q = q.clamp(0.0, 100.0) / 100.0;
+        Ok(self.parameters.map_axis(Axis(0), |column| {
+            let mut sorted = column.to_vec();
+            sorted.sort_by(f64::total_cmp);
+            let position = q * (sorted.len() - 1) as f64;
+            let lower = position.floor() as usize;
+            let upper = position.ceil() as usize;
+            let weight = position - lower as f64;
+            sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+        }))
+    }
+
+    /// Returns the lower and upper bounds of the central interval that
+    /// contains the fraction `confidence` of the samples of each parameter.
+    ///
+    /// Returns an error for jackknife results.
+    pub fn percentile_interval(
+        &self,
+        confidence: f64,
+    ) -> Result<(Array1<f64>, Array1<f64>), EstimatorError> {
+        let tail = 50.0 * (1.0 - confidence);
+        Ok((self.percentile(tail)?, self.percentile(100.0 - tail)?))
+    }
+}
+
+/// Bootstrap resampling of the data points within each `DataSet`.
+///
+/// Each sample draws as many data points as the original `DataSet`
+/// contains with replacement. The results are deterministic for a given seed.
+#[derive(Clone, Copy, Debug)]
+pub struct Bootstrap {
+    /// number of bootstrap samples
+    pub samples: usize,
+    /// seed of the random number generator
+    pub seed: u64,
+}
+
+impl Default for Bootstrap {
+    fn default() -> Self {
+        Self {
+            samples: 100,
+            seed: 0,
+        }
+    }
+}
+
+impl Bootstrap {
+    /// Set the number of bootstrap samples.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Set the seed of the random number generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Refit the parameters to bootstrap samples of the data in `estimator`.
+    ///
+    /// `fit` returns the optimal parameters for a resampled `Estimator`.
+    /// Fits that return an error are counted but not part of the result;
+    /// if all fits fail, an error is returned.
+    /// All `DataSet`s have to support [DataSet::subset](crate::DataSet::subset),
+    /// which is checked before the first fit.
+    pub fn run<U, E, F>(
+        &self,
+        estimator: &Estimator<U, E>,
+        mut fit: F,
+    ) -> Result<ResamplingResult, EstimatorError>
+    where
+        U: EosUnit + 'static,
+        E: EquationOfState + 'static,
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+        F: FnMut(&Estimator<U, E>) -> Result<Array1<f64>, EstimatorError>,
+    {
+        estimator.check_subsets()?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let datapoints: Vec<usize> = estimator
+            .datasets()
+            .iter()
+            .map(|d| d.datapoints())
+            .collect();
+        let mut parameters = Vec::with_capacity(self.samples);
+        let mut failed = 0;
+        for _ in 0..self.samples {
+            let indices: Vec<Vec<usize>> = datapoints
+                .iter()
+                .map(|&n| (0..n).map(|_| rng.gen_range(0..n)).collect())
+                .collect();
+            match fit(&estimator.subset(&indices)?) {
+                Ok(p) => parameters.push(p),
+                Err(_) => failed += 1,
+            }
+        }
+        ResamplingResult::new(parameters, failed, ResamplingMethod::Bootstrap)
+    }
+}
+
+/// Leave-one-dataset-out jackknife.
+///
+/// The parameters are refitted once for every `DataSet` in `estimator`
+/// with that `DataSet` removed. `fit` returns the optimal parameters for
+/// the reduced `Estimator`. Fits that return an error are counted but not
+/// part of the result; if all fits fail, an error is returned. At least two
+/// `DataSet`s are required.
+///
+/// The uncertainty of the parameters is given by [ResamplingResult::std];
+/// percentiles are not available for jackknife results.
+pub fn jackknife<U, E, F>(
+    estimator: &Estimator<U, E>,
+    mut fit: F,
+) -> Result<ResamplingResult, EstimatorError>
+where
+    U: EosUnit,
+    E: EquationOfState,
+    QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    F: FnMut(&Estimator<U, E>) -> Result<Array1<f64>, EstimatorError>,
+{
+    let data = estimator.datasets();
+    if data.len() < 2 {
+        return Err(EstimatorError::NotEnoughDataSets(data.len()));
+    }
+    let mut parameters = Vec::with_capacity(data.len());
+    let mut failed = 0;
+    for i in 0..data.len() {
+        let reduced = estimator.with_datasets(
+            data.iter()
+                .cloned()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .collect(),
+        );
+        match fit(&reduced) {
+            Ok(p) => parameters.push(p),
+            Err(_) => failed += 1,
+        }
+    }
+    ResamplingResult::new(parameters, failed, ResamplingMethod::Jackknife)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataSet, Loss, PredictionOptions, VaporPressure};
+    use feos_core::cubic::PengRobinson;
+    use ndarray::arr1;
+    use quantity::si::*;
+    use std::rc::Rc;
+
+    fn result(parameters: &[f64], method: ResamplingMethod) -> ResamplingResult {
+        let parameters = parameters.iter().map(|&p| arr1(&[p, 2.0 * p])).collect();
+        ResamplingResult::new(parameters, 0, method).unwrap()
+    }
+
+    #[test]
+    fn statistics() {
+        let bootstrap = result(&[1.0, 2.0, 3.0, 4.0], ResamplingMethod::Bootstrap);
+        assert_eq!(bootstrap.mean(), arr1(&[2.5, 5.0]));
+        let var: f64 = 5.0 / 3.0;
+        assert!((bootstrap.std()[0] - var.sqrt()).abs() < 1e-14);
+        assert_eq!(bootstrap.percentile(50.0).unwrap(), arr1(&[2.5, 5.0]));
+        assert_eq!(bootstrap.percentile(0.0).unwrap(), arr1(&[1.0, 2.0]));
+        let (lower, upper) = bootstrap.percentile_interval(0.5).unwrap();
+        assert_eq!(lower, arr1(&[1.75, 3.5]));
+        assert_eq!(upper, arr1(&[3.25, 6.5]));
+
+        let jackknife = result(&[1.0, 2.0, 3.0, 4.0], ResamplingMethod::Jackknife);
+        // (n-1)/n * sum of squared deviations
+        let var: f64 = 0.75 * 5.0;
+        assert!((jackknife.std()[0] - var.sqrt()).abs() < 1e-14);
+        assert!(matches!(
+            jackknife.percentile(50.0),
+            Err(EstimatorError::JackknifePercentile)
+        ));
+        assert!(jackknife.percentile_interval(0.9).is_err());
+    }
+
+    fn estimator(targets: &[&[f64]]) -> Estimator<SIUnit, PengRobinson> {
+        let data = targets
+            .iter()
+            .map(|target| {
+                let n = target.len();
+                let data = VaporPressure::new(
+                    arr1(target) * PASCAL,
+                    Array1::linspace(250.0, 300.0, n) * KELVIN,
+                    Default::default(),
+                    PredictionOptions::default(),
+                )
+                .unwrap();
+                Rc::new(data) as Rc<dyn DataSet<SIUnit, PengRobinson>>
+            })
+            .collect();
+        let n = targets.len();
+        Estimator::new(data, vec![1.0; n], vec![Loss::Linear; n])
+    }
+
+    /// "Fit" the mean of all targets.
+    fn mean_target(
+        estimator: &Estimator<SIUnit, PengRobinson>,
+    ) -> Result<Array1<f64>, EstimatorError> {
+        let targets = estimator
+            .datasets()
+            .iter()
+            .map(|d| d.target().to_reduced(PASCAL))
+            .collect::<Result<Vec<_>, _>>()?;
+        let n: usize = targets.iter().map(|t| t.len()).sum();
+        let sum: f64 = targets.iter().map(|t| t.sum()).sum();
+        Ok(arr1(&[sum / n as f64]))
+    }
+
+    #[test]
+    fn bootstrap() {
+        let estimator = estimator(&[&[1.0, 2.0, 3.0, 4.0, 5.0]]);
+        let bootstrap = Bootstrap::default().samples(200);
+        let result = bootstrap.run(&estimator, mean_target).unwrap();
+        assert_eq!(result.parameters.nrows(), 200);
+        assert_eq!(result.method, ResamplingMethod::Bootstrap);
+        // standard error of the mean: sqrt(2 / 5) = 0.63
+        assert!((result.mean()[0] - 3.0).abs() < 0.2);
+        assert!((result.std()[0] - 0.63).abs() < 0.15);
+        let again = bootstrap.run(&estimator, mean_target).unwrap();
+        assert_eq!(result.parameters, again.parameters);
+
+        let mut calls = 0;
+        let failing = bootstrap.samples(3).run(&estimator, |_| {
+            calls += 1;
+            if calls == 2 {
+                Err(EstimatorError::IncompatibleInput)
+            } else {
+                Ok(arr1(&[1.0]))
+            }
+        });
+        let failing = failing.unwrap();
+        assert_eq!(failing.failed, 1);
+        assert_eq!(failing.parameters.nrows(), 2);
+
+        let all_failing = bootstrap
+            .samples(3)
+            .run(&estimator, |_| Err(EstimatorError::IncompatibleInput));
+        assert!(matches!(all_failing, Err(EstimatorError::AllFitsFailed(3))));
+    }
+
+    #[test]
+    fn leave_one_dataset_out() {
+        let estimator = estimator(&[&[1.0], &[2.0], &[6.0]]);
+        let result = jackknife(&estimator, mean_target).unwrap();
+        assert_eq!(result.method, ResamplingMethod::Jackknife);
+        assert_eq!(result.parameters.column(0), arr1(&[4.0, 3.5, 1.5]));
+        let single = Estimator::<SIUnit, PengRobinson>::new(
+            estimator.datasets()[..1].to_vec(),
+            vec![1.0],
+            vec![Loss::Linear],
+        );
+        assert!(matches!(
+            jackknife(&single, mean_target),
+            Err(EstimatorError::NotEnoughDataSets(1))
+        ));
+    }
+}
@@ -0,0 +1,308 @@
+use super::{DataSet, EstimatorError, Loss, Prediction};
+use crate::dataset::{join, select};
+use feos_core::{
+    Contributions, DensityInitialization, EosUnit, EquationOfState, MolarWeight, State,
+};
+use ndarray::{arr1, Array1};
+use quantity::{QuantityArray1, QuantityScalar};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A function that evaluates a property of a [State].
+pub type StateProperty<U, E> = dyn Fn(&State<U, E>) -> QuantityScalar<U>;
+
+/// Store experimental data of an arbitrary property that can be
+/// computed from a [State] given temperature and pressure.
+#[derive(Clone)]
+pub struct StatePropertyDataSet<U: EosUnit, E> {
+    /// property
+    pub target: QuantityArray1<U>,
+    /// description of the property
+    target_str: String,
+    /// temperature
+    temperature: QuantityArray1<U>,
+    /// pressure
+    pressure: QuantityArray1<U>,
+    /// initialization of the density iteration
+    density_initialization: DensityInitialization<U>,
+    /// function that evaluates the property
+    property: Rc<StateProperty<U, E>>,
+    /// name of the property if it was created from its name
+    property_name: Option<String>,
+    /// number of data points
+    datapoints: usize,
+}
+
+impl<U: EosUnit, E: EquationOfState> StatePropertyDataSet<U, E> {
+    /// Create a new data set for a property that is evaluated by `property`.
+    ///
+    /// The states are created from temperature and pressure with
+    /// the given `density_initialization`.
+    pub fn new(
+        target: QuantityArray1<U>,
+        target_str: &str,
+        temperature: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        density_initialization: DensityInitialization<U>,
+        property: Box<StateProperty<U, E>>,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature.len() != datapoints || pressure.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        Ok(Self {
+            target,
+            target_str: target_str.to_owned(),
+            temperature,
+            pressure,
+            density_initialization,
+            property: Rc::from(property),
+            property_name: None,
+            datapoints,
+        })
+    }
+
+    /// Return temperature.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.temperature.clone()
+    }
+
+    /// Return pressure.
+    pub fn pressure(&self) -> QuantityArray1<U> {
+        self.pressure.clone()
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    ///
+    /// Both data sets have to be created from the same property name
+    /// or evaluate the same property function.
+    /// The density initialization of `self` is used for all data points.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        let same_property = match (&self.property_name, &other.property_name) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => Rc::ptr_eq(&self.property, &other.property),
+            _ => false,
+        };
+        if !same_property || self.target_str != other.target_str {
+            return Err(EstimatorError::IncompatibleDataSets);
+        }
+        let target = join(&self.target, &other.target)?;
+        Ok(Self {
+            datapoints: target.len(),
+            target,
+            target_str: self.target_str.clone(),
+            temperature: join(&self.temperature, &other.temperature)?,
+            pressure: join(&self.pressure, &other.pressure)?,
+            density_initialization: self.density_initialization,
+            property: self.property.clone(),
+            property_name: self.property_name.clone(),
+        })
+    }
+}
+
+impl<U: EosUnit, E: EquationOfState + MolarWeight<U>> StatePropertyDataSet<U, E> {
+    /// Names of the properties that can be used in
+    /// [StatePropertyDataSet::from_property_name].
+    pub const PROPERTY_NAMES: [&'static str; 9] = [
+        "density",
+        "mass_density",
+        "compressibility_factor",
+        "molar_isochoric_heat_capacity",
+        "molar_isobaric_heat_capacity",
+        "isothermal_compressibility",
+        "isentropic_compressibility",
+        "joule_thomson_coefficient",
+        "speed_of_sound",
+    ];
+
+    /// Create a new data set for a property given by its name.
+    ///
+    /// Available properties are listed in [StatePropertyDataSet::PROPERTY_NAMES].
+    pub fn from_property_name(
+        target: QuantityArray1<U>,
+        property_name: &str,
+        temperature: QuantityArray1<U>,
+        pressure: QuantityArray1<U>,
+        density_initialization: DensityInitialization<U>,
+    ) -> Result<Self, EstimatorError> {
+        let c = Contributions::Total;
+        let property: Box<StateProperty<U, E>> = match property_name {
+            "density" => Box::new(|s| s.density),
+            "mass_density" => Box::new(|s| s.mass_density()),
+            "compressibility_factor" => Box::new(move |s| s.compressibility(c).into()),
+            "molar_isochoric_heat_capacity" => Box::new(move |s| s.c_v(c)),
+            "molar_isobaric_heat_capacity" => Box::new(move |s| s.c_p(c)),
+            "isothermal_compressibility" => Box::new(|s| s.isothermal_compressibility()),
+            "isentropic_compressibility" => Box::new(|s| s.isentropic_compressibility()),
+            "joule_thomson_coefficient" => Box::new(|s| s.joule_thomson()),
+            "speed_of_sound" => Box::new(|s| s.speed_of_sound()),
+            _ => return Err(EstimatorError::UnknownProperty(property_name.to_owned())),
+        };
+        let mut dataset = Self::new(
+            target,
+            &property_name.replace('_', " "),
+            temperature,
+            pressure,
+            density_initialization,
+            property,
+        )?;
+        dataset.property_name = Some(property_name.to_owned());
+        Ok(dataset)
+    }
+}
+
+impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for StatePropertyDataSet<U, E> {
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        &self.target_str
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        vec!["temperature", "pressure"]
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let moles = arr1(&[1.0]) * U::reference_moles();
+        Prediction::from_results(
+            (0..self.datapoints).map(|i| {
+                let t = self.temperature.get(i);
+                let p = self.pressure.get(i);
+                State::new_npt(eos, t, p, &moles, self.density_initialization)
+                    .map(|state| (self.property)(&state))
+            }),
+            self.target.get(0),
+        )
+    }
+
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut cost = self.relative_difference(eos)?;
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        let target = select(&self.target, indices)?;
+        Ok(Rc::new(Self {
+            datapoints: target.len(),
+            target,
+            target_str: self.target_str.clone(),
+            temperature: select(&self.temperature, indices)?,
+            pressure: select(&self.pressure, indices)?,
+            density_initialization: self.density_initialization,
+            property: self.property.clone(),
+            property_name: self.property_name.clone(),
+        }))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(2);
+        m.insert("temperature".to_owned(), self.temperature());
+        m.insert("pressure".to_owned(), self.pressure());
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane;
+    use feos_core::cubic::PengRobinson;
+    use quantity::si::*;
+
+    #[test]
+    fn closure_and_property_name() {
+        let eos = propane();
+        let temperature = arr1(&[250.0, 300.0]) * KELVIN;
+        let pressure = arr1(&[1e6, 2e6]) * PASCAL;
+        let target = arr1(&[1.0, 1.0]) * MOL / METER.powi(3);
+        let by_name = StatePropertyDataSet::from_property_name(
+            target.clone(),
+            "density",
+            temperature.clone(),
+            pressure.clone(),
+            DensityInitialization::Liquid,
+        )
+        .unwrap();
+        assert_eq!(
+            DataSet::<SIUnit, PengRobinson>::target_str(&by_name),
+            "density"
+        );
+        let by_closure = StatePropertyDataSet::new(
+            target,
+            "density",
+            temperature,
+            pressure,
+            DensityInitialization::Liquid,
+            Box::new(|s: &State<SIUnit, PengRobinson>| s.density),
+        )
+        .unwrap();
+        let a = by_name.predict(&eos).unwrap();
+        let b = by_closure.predict(&eos).unwrap();
+        for i in 0..2 {
+            assert_eq!(a.get(i), b.get(i));
+        }
+
+        assert!(matches!(
+            by_name.concatenate(&by_closure),
+            Err(EstimatorError::IncompatibleDataSets)
+        ));
+        assert_eq!(by_name.concatenate(&by_name).unwrap().datapoints, 4);
+        assert_eq!(by_closure.concatenate(&by_closure).unwrap().datapoints, 4);
+        let subset = by_closure.subset(&[1]).unwrap();
+        assert_eq!(subset.predict(&eos).unwrap().get(0), b.get(1));
+    }
+
+    #[test]
+    fn concatenate_by_name() {
+        let dataset = |property_name: &str, temperature: f64| {
+            StatePropertyDataSet::<SIUnit, PengRobinson>::from_property_name(
+                arr1(&[1.0]) * MOL / METER.powi(3),
+                property_name,
+                arr1(&[temperature]) * KELVIN,
+                arr1(&[1e6]) * PASCAL,
+                DensityInitialization::Liquid,
+            )
+            .unwrap()
+        };
+        let joined = dataset("density", 250.0)
+            .concatenate(&dataset("density", 300.0))
+            .unwrap();
+        assert_eq!(joined.temperature(), arr1(&[250.0, 300.0]) * KELVIN);
+        assert!(matches!(
+            dataset("density", 250.0).concatenate(&dataset("mass_density", 300.0)),
+            Err(EstimatorError::IncompatibleDataSets)
+        ));
+    }
+
+    #[test]
+    fn unknown_property() {
+        let result = StatePropertyDataSet::<SIUnit, PengRobinson>::from_property_name(
+            arr1(&[1.0]) * KELVIN,
+            "enthalpy_of_vaporization",
+            arr1(&[250.0]) * KELVIN,
+            arr1(&[1e6]) * PASCAL,
+            DensityInitialization::Liquid,
+        );
+        assert!(matches!(result, Err(EstimatorError::UnknownProperty(_))));
+    }
+}
@@ -0,0 +1,137 @@
+use ndarray::Array1;
+use std::fmt;
+
+/// Statistics of the relative differences between the predictions
+/// of an equation of state and the target values of a `DataSet`.
+///
+/// Failed predictions (`NAN`) are only counted in `failed` and
+/// excluded from all other statistics.
+#[derive(Clone, Debug)]
+pub struct DeviationStatistics {
+    /// number of data points
+    pub datapoints: usize,
+    /// number of data points for which the prediction failed
+    pub failed: usize,
+    /// mean relative difference (bias)
+    pub mrd: f64,
+    /// mean absolute relative difference
+    pub mard: f64,
+    /// root mean square of the relative differences
+    pub rms: f64,
+    /// median of the absolute relative differences
+    pub median: f64,
+    /// maximum absolute relative difference
+    pub max: f64,
+    /// index of the data point with the maximum absolute relative difference
+    pub max_index: Option<usize>,
+    /// tolerance of the absolute relative difference
+    pub tolerance: f64,
+    /// fraction of all data points with an absolute relative difference
+    /// smaller than or equal to `tolerance`
+    pub within_tolerance: f64,
+}
+
+impl DeviationStatistics {
+    /// Calculate the statistics from the relative differences.
+    pub fn new(relative_difference: &Array1<f64>, tolerance: f64) -> Self {
+        let datapoints = relative_difference.len();
+        let finite: Vec<(usize, f64)> = relative_difference
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, x)| x.is_finite())
+            .collect();
+        let n = finite.len() as f64;
+        let mrd = finite.iter().map(|(_, x)| x).sum::<f64>() / n;
+        let mard = finite.iter().map(|(_, x)| x.abs()).sum::<f64>() / n;
+        let rms = (finite.iter().map(|(_, x)| x * x).sum::<f64>() / n).sqrt();
+        let (max_index, max) = match finite
+            .iter()
+            .map(|&(i, x)| (i, x.abs()))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+        {
+            Some((i, x)) => (Some(i), x),
+            None => (None, f64::NAN),
+        };
+        let mut absolute: Vec<f64> = finite.iter().map(|(_, x)| x.abs()).collect();
+        absolute.sort_by(f64::total_cmp);
+        let median = match absolute.len() {
+            0 => f64::NAN,
+            k if k % 2 == 1 => absolute[k / 2],
+            k => 0.5 * (absolute[k / 2 - 1] + absolute[k / 2]),
+        };
+        let within = absolute.iter().filter(|&&x| x <= tolerance).count();
+        Self {
+            datapoints,
+            failed: datapoints - finite.len(),
+            mrd,
+            mard,
+            rms,
+            median,
+            max,
+            max_index,
+            tolerance,
+            within_tolerance: within as f64 / datapoints as f64,
+        }
+    }
+}
+
+impl fmt::Display for DeviationStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DeviationStatistics(datapoints: {}, failed: {}, MRD: {:.4}, MARD: {:.4}, RMS: {:.4}, median: {:.4}, max: {:.4}",
+            self.datapoints, self.failed, self.mrd, self.mard, self.rms, self.median, self.max
+        )?;
+        if let Some(i) = self.max_index {
+            write!(f, " at index {}", i)?;
+        }
+        write!(
+            f,
+            ", within {}: {:.4})",
+            self.tolerance, self.within_tolerance
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    #[test]
+    fn statistics() {
+        let x = arr1(&[0.1, -0.3, f64::NAN, 0.2, 0.0]);
+        let s = DeviationStatistics::new(&x, 0.15);
+        assert_eq!(s.datapoints, 5);
+        assert_eq!(s.failed, 1);
+        assert!(s.mrd.abs() < 1e-15);
+        assert!((s.mard - 0.15).abs() < 1e-15);
+        assert!((s.rms - (0.14f64 / 4.0).sqrt()).abs() < 1e-15);
+        assert!((s.median - 0.15).abs() < 1e-15);
+        assert_eq!(s.max, 0.3);
+        assert_eq!(s.max_index, Some(1));
+        // failed data points count as outside of the tolerance
+        assert_eq!(s.within_tolerance, 0.4);
+    }
+
+    #[test]
+    fn odd_number_of_datapoints() {
+        let s = DeviationStatistics::new(&arr1(&[-0.5, 0.2, 0.1]), 0.5);
+        assert_eq!(s.median, 0.2);
+        assert_eq!(s.max_index, Some(0));
+        assert_eq!(s.within_tolerance, 1.0);
+    }
+
+    #[test]
+    fn all_failed() {
+        let s = DeviationStatistics::new(&arr1(&[f64::NAN, f64::INFINITY]), 0.1);
+        assert_eq!(s.failed, 2);
+        assert!(s.mard.is_nan());
+        assert!(s.median.is_nan());
+        assert!(s.max.is_nan());
+        assert_eq!(s.max_index, None);
+        assert_eq!(s.within_tolerance, 0.0);
+        assert!(!s.to_string().contains("at index"));
+    }
+}
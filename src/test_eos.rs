@@ -0,0 +1,117 @@
+//! Equations of state for the unit tests.
+use feos_core::cubic::{PengRobinson, PengRobinsonParameters};
+use feos_core::{
+    EntropyScaling, EosResult, EquationOfState, HelmholtzEnergy, IdealGasContribution, MolarWeight,
+};
+use ndarray::Array1;
+use quantity::si::*;
+use std::rc::Rc;
+
+fn propane_parameters(acentric_factor: f64) -> PengRobinsonParameters {
+    PengRobinsonParameters::new_simple(&[369.96], &[42.5e5], &[acentric_factor], &[44.0962])
+        .unwrap()
+}
+
+/// Peng-Robinson equation of state of propane.
+pub fn propane() -> Rc<PengRobinson> {
+    propane_with_acentric_factor(0.153)
+}
+
+/// Peng-Robinson equation of state of propane with a modified acentric factor.
+pub fn propane_with_acentric_factor(acentric_factor: f64) -> Rc<PengRobinson> {
+    Rc::new(PengRobinson::new(Rc::new(propane_parameters(
+        acentric_factor,
+    ))))
+}
+
+/// Peng-Robinson equation of state of a mixture of two identical propane components.
+pub fn propane_mixture() -> Rc<PengRobinson> {
+    let parameters =
+        PengRobinsonParameters::new_simple(&[369.96; 2], &[42.5e5; 2], &[0.153; 2], &[44.0962; 2])
+            .unwrap();
+    Rc::new(PengRobinson::new(Rc::new(parameters)))
+}
+
+/// Peng-Robinson equation of state of propane with the entropy scaling
+/// correlation `ln(Y / Y_ref) = c_0 + c_1 s + c_2 s²` for all transport
+/// properties and constant reference values.
+pub struct PropaneEntropyScaling {
+    pr: PengRobinson,
+    pub coefficients: [f64; 3],
+}
+
+impl PropaneEntropyScaling {
+    pub fn new(coefficients: [f64; 3]) -> Rc<Self> {
+        Rc::new(Self {
+            pr: PengRobinson::new(Rc::new(propane_parameters(0.153))),
+            coefficients,
+        })
+    }
+
+    fn correlation(&self, s_res: f64) -> EosResult<f64> {
+        let [c0, c1, c2] = self.coefficients;
+        Ok(c0 + c1 * s_res + c2 * s_res.powi(2))
+    }
+}
+
+impl EquationOfState for PropaneEntropyScaling {
+    fn components(&self) -> usize {
+        self.pr.components()
+    }
+
+    fn subset(&self, component_list: &[usize]) -> Self {
+        Self {
+            pr: self.pr.subset(component_list),
+            coefficients: self.coefficients,
+        }
+    }
+
+    fn compute_max_density(&self, moles: &Array1<f64>) -> f64 {
+        self.pr.compute_max_density(moles)
+    }
+
+    fn residual(&self) -> &[Box<dyn HelmholtzEnergy>] {
+        self.pr.residual()
+    }
+
+    fn ideal_gas(&self) -> &dyn IdealGasContribution {
+        self.pr.ideal_gas()
+    }
+}
+
+impl MolarWeight<SIUnit> for PropaneEntropyScaling {
+    fn molar_weight(&self) -> SIArray1 {
+        self.pr.molar_weight()
+    }
+}
+
+impl EntropyScaling<SIUnit> for PropaneEntropyScaling {
+    fn viscosity_reference(&self, _: SINumber, _: SINumber, _: &SIArray1) -> EosResult<SINumber> {
+        Ok(1e-5 * PASCAL * SECOND)
+    }
+
+    fn viscosity_correlation(&self, s_res: f64, _: &Array1<f64>) -> EosResult<f64> {
+        self.correlation(s_res)
+    }
+
+    fn diffusion_reference(&self, _: SINumber, _: SINumber, _: &SIArray1) -> EosResult<SINumber> {
+        Ok(1e-8 * METER.powi(2) / SECOND)
+    }
+
+    fn diffusion_correlation(&self, s_res: f64, _: &Array1<f64>) -> EosResult<f64> {
+        self.correlation(s_res)
+    }
+
+    fn thermal_conductivity_reference(
+        &self,
+        _: SINumber,
+        _: SINumber,
+        _: &SIArray1,
+    ) -> EosResult<SINumber> {
+        Ok(1e-2 * WATT / METER / KELVIN)
+    }
+
+    fn thermal_conductivity_correlation(&self, s_res: f64, _: &Array1<f64>) -> EosResult<f64> {
+        self.correlation(s_res)
+    }
+}
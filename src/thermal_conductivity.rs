@@ -1,5 +1,9 @@
-use super::{DataSet, EstimatorError, Loss};
-use feos_core::{DensityInitialization, EntropyScaling, EosUnit, EquationOfState, State};
+use super::{DataSet, EstimatorError, Loss, Prediction, PredictionOptions};
+use crate::dataset::{join, select};
+use feos_core::{
+    Contributions, DensityInitialization, EntropyScaling, EosResult, EosUnit, EquationOfState,
+    PhaseEquilibrium, State,
+};
 use ndarray::{arr1, Array1};
 use quantity::{QuantityArray1, QuantityScalar};
 use std::collections::HashMap;
@@ -10,22 +14,41 @@ use std::rc::Rc;
 pub struct ThermalConductivity<U: EosUnit> {
     pub target: QuantityArray1<U>,
     temperature: QuantityArray1<U>,
-    pressure: QuantityArray1<U>,
+    pressure_or_density: QuantityArray1<U>,
+    density_initialization: DensityInitialization<U>,
+    isobaric: bool,
     datapoints: usize,
 }
 
 impl<U: EosUnit> ThermalConductivity<U> {
     /// Create a new data set for experimental thermal conductivity data.
+    ///
+    /// The second input can either be the pressure or the (molar) density.
+    /// For pressure input, `density_initialization` determines the
+    /// phase in which the state is created. For density input, it is ignored.
     pub fn new(
         target: QuantityArray1<U>,
         temperature: QuantityArray1<U>,
-        pressure: QuantityArray1<U>,
+        pressure_or_density: QuantityArray1<U>,
+        density_initialization: DensityInitialization<U>,
     ) -> Result<Self, EstimatorError> {
         let datapoints = target.len();
+        if temperature.len() != datapoints || pressure_or_density.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        let isobaric = if pressure_or_density.has_unit(&U::reference_pressure()) {
+            true
+        } else if pressure_or_density.has_unit(&U::reference_density()) {
+            false
+        } else {
+            return Err(EstimatorError::NoPressureOrDensity);
+        };
         Ok(Self {
             target,
             temperature,
-            pressure,
+            pressure_or_density,
+            density_initialization,
+            isobaric,
             datapoints,
         })
     }
@@ -35,9 +58,46 @@ impl<U: EosUnit> ThermalConductivity<U> {
         self.temperature.clone()
     }
 
-    /// Return pressure.
-    pub fn pressure(&self) -> QuantityArray1<U> {
-        self.pressure.clone()
+    /// Return pressure or density.
+    pub fn pressure_or_density(&self) -> QuantityArray1<U> {
+        self.pressure_or_density.clone()
+    }
+
+    /// Description of the second input.
+    fn specification_str(&self) -> &str {
+        if self.isobaric {
+            "pressure"
+        } else {
+            "density"
+        }
+    }
+
+    /// Create the state of data point `i`.
+    fn state<E: EquationOfState>(&self, eos: &Rc<E>, i: usize) -> EosResult<State<U, E>>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let moles = arr1(&[1.0]) * U::reference_moles();
+        let t = self.temperature.get(i);
+        if self.isobaric {
+            let p = self.pressure_or_density.get(i);
+            State::new_npt(eos, t, p, &moles, self.density_initialization)
+        } else {
+            let v = U::reference_moles() / self.pressure_or_density.get(i);
+            State::new_nvt(eos, t, v, &moles)
+        }
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    ///
+    /// The density initialization of `self` is used for all data points.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            join(&self.pressure_or_density, &other.pressure_or_density)?,
+            self.density_initialization,
+        )
     }
 }
 
@@ -51,23 +111,186 @@ impl<U: EosUnit, E: EquationOfState + EntropyScaling<U>> DataSet<U, E> for Therm
     }
 
     fn input_str(&self) -> Vec<&str> {
-        vec!["temperature", "pressure"]
+        vec!["temperature", self.specification_str()]
     }
 
     fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
     where
         QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
     {
-        let unit = self.target.get(0);
-        let mut prediction = Array1::zeros(self.datapoints) * unit;
-        let moles = arr1(&[1.0]) * U::reference_moles();
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Prediction::from_results(
+            (0..self.datapoints).map(|i| {
+                self.state(eos, i)
+                    .and_then(|state| state.thermal_conductivity())
+            }),
+            self.target.get(0),
+        )
+    }
+
+    fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut cost = self.relative_difference(eos)?;
+        loss.apply(&mut cost.view_mut());
+        Ok(cost / self.datapoints as f64)
+    }
+
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            select(&self.pressure_or_density, indices)?,
+            self.density_initialization,
+        )?))
+    }
+
+    fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
+        let mut m = HashMap::with_capacity(2);
+        m.insert("temperature".to_owned(), self.temperature());
+        m.insert(
+            self.specification_str().to_owned(),
+            self.pressure_or_density(),
+        );
+        m
+    }
+
+    fn entropy_scaling_data(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<(Array1<f64>, Array1<f64>), EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut s_res = Array1::from_elem(self.datapoints, f64::NAN);
+        let mut ln_y = Array1::from_elem(self.datapoints, f64::NAN);
         for i in 0..self.datapoints {
-            let t = self.temperature.get(i);
-            let p = self.pressure.get(i);
-            let state = State::new_npt(eos, t, p, &moles, DensityInitialization::None)?;
-            prediction.try_set(i, state.thermal_conductivity()?)?;
+            if let Ok(state) = self.state(eos, i) {
+                s_res[i] = state
+                    .molar_entropy(Contributions::ResidualNvt)
+                    .to_reduced(U::reference_molar_entropy())?;
+                ln_y[i] = (self.target.get(i) / state.thermal_conductivity_reference()?)
+                    .into_value()?
+                    .ln();
+            }
+        }
+        Ok((s_res, ln_y))
+    }
+}
+
+/// Store experimental thermal conductivity data at vapor liquid equilibrium.
+#[derive(Clone)]
+pub struct EquilibriumThermalConductivity<U: EosUnit> {
+    pub target: QuantityArray1<U>,
+    temperature: QuantityArray1<U>,
+    liquid: bool,
+    datapoints: usize,
+    options: PredictionOptions,
+}
+
+impl<U: EosUnit> EquilibriumThermalConductivity<U> {
+    /// Create a new data set for experimental thermal conductivity data
+    /// of the saturated liquid (`liquid = true`) or the saturated vapor.
+    pub fn new(
+        target: QuantityArray1<U>,
+        temperature: QuantityArray1<U>,
+        liquid: bool,
+        options: PredictionOptions,
+    ) -> Result<Self, EstimatorError> {
+        let datapoints = target.len();
+        if temperature.len() != datapoints {
+            return Err(EstimatorError::IncompatibleInput);
+        }
+        Ok(Self {
+            target,
+            temperature,
+            liquid,
+            datapoints,
+            options,
+        })
+    }
+
+    /// Return temperature.
+    pub fn temperature(&self) -> QuantityArray1<U> {
+        self.temperature.clone()
+    }
+
+    /// Create the saturated state of data point `i`.
+    fn state<E: EquationOfState>(&self, eos: &Rc<E>, i: usize) -> EosResult<State<U, E>>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let t = self.temperature.get(i);
+        let vle = PhaseEquilibrium::pure(eos, t, None, self.options.phase_equilibrium)?;
+        if self.liquid {
+            Ok(vle.liquid().clone())
+        } else {
+            Ok(vle.vapor().clone())
+        }
+    }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        if self.liquid != other.liquid {
+            return Err(EstimatorError::IncompatibleDataSets);
         }
-        Ok(prediction)
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            self.liquid,
+            self.options,
+        )
+    }
+}
+
+impl<U: EosUnit, E: EquationOfState + EntropyScaling<U>> DataSet<U, E>
+    for EquilibriumThermalConductivity<U>
+{
+    fn target(&self) -> QuantityArray1<U> {
+        self.target.clone()
+    }
+
+    fn target_str(&self) -> &str {
+        if self.liquid {
+            "equilibrium liquid thermal conductivity"
+        } else {
+            "equilibrium vapor thermal conductivity"
+        }
+    }
+
+    fn input_str(&self) -> Vec<&str> {
+        vec!["temperature"]
+    }
+
+    fn predict(&self, eos: &Rc<E>) -> Result<QuantityArray1<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Ok(self.predict_with_status(eos)?.values)
+    }
+
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        Prediction::from_results(
+            (0..self.datapoints).map(|i| {
+                self.state(eos, i)
+                    .and_then(|state| state.thermal_conductivity())
+            }),
+            self.target.get(0),
+        )
     }
 
     fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
@@ -79,10 +302,44 @@ impl<U: EosUnit, E: EquationOfState + EntropyScaling<U>> DataSet<U, E> for Therm
         Ok(cost / self.datapoints as f64)
     }
 
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            self.liquid,
+            self.options,
+        )?))
+    }
+
     fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
         let mut m = HashMap::with_capacity(1);
         m.insert("temperature".to_owned(), self.temperature());
-        m.insert("pressure".to_owned(), self.pressure());
         m
     }
+
+    fn entropy_scaling_data(
+        &self,
+        eos: &Rc<E>,
+    ) -> Result<(Array1<f64>, Array1<f64>), EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut s_res = Array1::from_elem(self.datapoints, f64::NAN);
+        let mut ln_y = Array1::from_elem(self.datapoints, f64::NAN);
+        for i in 0..self.datapoints {
+            if let Ok(state) = self.state(eos, i) {
+                s_res[i] = state
+                    .molar_entropy(Contributions::ResidualNvt)
+                    .to_reduced(U::reference_molar_entropy())?;
+                ln_y[i] = (self.target.get(i) / state.thermal_conductivity_reference()?)
+                    .into_value()?
+                    .ln();
+            }
+        }
+        Ok((s_res, ln_y))
+    }
 }
@@ -1,10 +1,124 @@
-use super::{DataSet, EstimatorError, Loss};
-use feos_core::{Contributions, EosUnit, EquationOfState, PhaseEquilibrium, SolverOptions, State};
+use super::{DataSet, EstimatorError, Loss, Prediction, PredictionOptions, PredictionStatus};
+use crate::dataset::{join, select};
+use feos_core::{Contributions, EosUnit, EquationOfState, PhaseEquilibrium, State};
 use ndarray::Array1;
 use quantity::{QuantityArray1, QuantityScalar};
 use std::collections::HashMap;
 use std::rc::Rc;
 
+/// Estimation of the vapor pressure above the critical temperature of the model.
+///
+/// All strategies are continuous at the critical point of the model. They are
+/// evaluated in terms of ln(p) and only used for temperatures at which the
+/// phase equilibrium of the equation of state can not be calculated.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Extrapolation {
+    /// no extrapolation, the vapor pressure is set to `NAN`
+    #[default]
+    None,
+    /// straight line of ln(p) over 1/T through the
+    /// model at 0.9 Tc and the critical point
+    ClausiusClapeyron,
+    /// least squares fit of ln(p) over 1/T to the given number (at least 2)
+    /// of model points evenly spaced between 0.7 Tc and the critical point
+    ClausiusClapeyronFit(usize),
+    /// Antoine equation ln(p) = A - B / (T + C) through the model
+    /// at 0.7 Tc, 0.85 Tc and the critical point
+    Antoine,
+    /// ln(p) decreases from the critical pressure of the model with the
+    /// given slope times (T - Tc) / Tc, so that the deviation grows
+    /// continuously with the distance from the critical temperature
+    Penalty(f64),
+}
+
+impl Extrapolation {
+    /// Returns the reduced ln(p) as function of the reduced temperature
+    /// or `None` if no extrapolation is used.
+    fn ln_pressure<U: EosUnit, E: EquationOfState>(
+        &self,
+        eos: &Rc<E>,
+        initial_temperature: QuantityScalar<U>,
+        options: PredictionOptions,
+    ) -> Result<Option<LnPressure>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        if let Self::None = self {
+            return Ok(None);
+        }
+        let critical_point =
+            State::critical_point(eos, None, Some(initial_temperature), options.critical_point)?;
+        let tc = critical_point
+            .temperature
+            .to_reduced(U::reference_temperature())?;
+        let ln_pc = critical_point
+            .pressure(Contributions::Total)
+            .to_reduced(U::reference_pressure())?
+            .ln();
+        let model = |t: f64| -> Result<(f64, f64), EstimatorError> {
+            let vle = PhaseEquilibrium::pure(
+                eos,
+                t * U::reference_temperature(),
+                None,
+                options.phase_equilibrium,
+            )?;
+            let p = vle.vapor().pressure(Contributions::Total);
+            Ok((t, p.to_reduced(U::reference_pressure())?.ln()))
+        };
+
+        let ln_p: LnPressure = match *self {
+            Self::None => unreachable!(),
+            Self::ClausiusClapeyron => {
+                let (a, b) = clausius_clapeyron(&[model(0.9 * tc)?, (tc, ln_pc)]);
+                Box::new(move |t| a + b / t)
+            }
+            Self::ClausiusClapeyronFit(points) => {
+                let n = points.max(2);
+                let mut data = (0..n - 1)
+                    .map(|k| model(tc * (0.7 + 0.3 * k as f64 / (n - 1) as f64)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                data.push((tc, ln_pc));
+                let (a, b) = clausius_clapeyron(&data);
+                Box::new(move |t| a + b / t)
+            }
+            Self::Antoine => {
+                let (t1, y1) = model(0.7 * tc)?;
+                let (t2, y2) = model(0.85 * tc)?;
+                let (t3, y3) = (tc, ln_pc);
+                let r = (y1 - y2) * (t2 - t3) / ((y2 - y3) * (t1 - t2));
+                let c = (t3 - r * t1) / (r - 1.0);
+                if c.is_finite() && t1 + c > 0.0 {
+                    let b = (y1 - y2) / (1.0 / (t2 + c) - 1.0 / (t1 + c));
+                    let a = y1 + b / (t1 + c);
+                    Box::new(move |t| a - b / (t + c))
+                } else {
+                    // the model points are (almost) on a Clausius-Clapeyron line
+                    let (a, b) = clausius_clapeyron(&[(t1, y1), (t3, y3)]);
+                    Box::new(move |t| a + b / t)
+                }
+            }
+            Self::Penalty(slope) => Box::new(move |t| ln_pc - slope * (t - tc) / tc),
+        };
+        Ok(Some(ln_p))
+    }
+}
+
+/// Reduced ln(p) as function of the reduced temperature.
+type LnPressure = Box<dyn Fn(f64) -> f64>;
+
+/// Least squares fit of `ln_p = a + b / t` to the points `(t, ln_p)`.
+fn clausius_clapeyron(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let x_mean = points.iter().map(|(t, _)| 1.0 / t).sum::<f64>() / n;
+    let y_mean = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (sxy, sxx) = points.iter().fold((0.0, 0.0), |(sxy, sxx), (t, y)| {
+        let dx = 1.0 / t - x_mean;
+        (sxy + dx * (y - y_mean), sxx + dx * dx)
+    });
+    let b = sxy / sxx;
+    (y_mean - b * x_mean, b)
+}
+
 /// Store experimental vapor pressure data.
 #[derive(Clone)]
 pub struct VaporPressure<U: EosUnit> {
@@ -12,7 +126,8 @@ pub struct VaporPressure<U: EosUnit> {
     temperature: QuantityArray1<U>,
     max_temperature: QuantityScalar<U>,
     datapoints: usize,
-    extrapolate: bool,
+    extrapolation: Extrapolation,
+    options: PredictionOptions,
 }
 
 impl<U: EosUnit> VaporPressure<U> {
@@ -20,14 +135,12 @@ impl<U: EosUnit> VaporPressure<U> {
     ///
     /// If the equation of state fails to compute the vapor pressure
     /// (e.g. when it underestimates the critical point) the vapor
-    /// pressure can be estimated.
-    /// If `extrapolate` is `true`, the vapor pressure is estimated by
-    /// calculating the slope of ln(p) over 1/T.
-    /// If `extrapolate` is `false`, it is set to `NAN`.
+    /// pressure is estimated according to `extrapolation`.
     pub fn new(
         target: QuantityArray1<U>,
         temperature: QuantityArray1<U>,
-        extrapolate: bool,
+        extrapolation: Extrapolation,
+        options: PredictionOptions,
     ) -> Result<Self, EstimatorError> {
         let datapoints = target.len();
         let max_temperature = temperature
@@ -41,7 +154,8 @@ impl<U: EosUnit> VaporPressure<U> {
             temperature,
             max_temperature,
             datapoints,
-            extrapolate,
+            extrapolation,
+            options,
         })
     }
 
@@ -49,6 +163,19 @@ impl<U: EosUnit> VaporPressure<U> {
     pub fn temperature(&self) -> QuantityArray1<U> {
         self.temperature.clone()
     }
+
+    /// Concatenate the data points of `self` and `other`.
+    pub fn concatenate(&self, other: &Self) -> Result<Self, EstimatorError> {
+        if self.extrapolation != other.extrapolation {
+            return Err(EstimatorError::IncompatibleDataSets);
+        }
+        Self::new(
+            join(&self.target, &other.target)?,
+            join(&self.temperature, &other.temperature)?,
+            self.extrapolation,
+            self.options,
+        )
+    }
 }
 
 impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for VaporPressure<U> {
@@ -68,36 +195,47 @@ impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for VaporPressure<U> {
     where
         QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
     {
-        let critical_point = State::critical_point(
-            eos,
-            None,
-            Some(self.max_temperature),
-            SolverOptions::default(),
-        )?;
-        let tc = critical_point.temperature;
-        let pc = critical_point.pressure(Contributions::Total);
-
-        let t0 = 0.9 * tc;
-        let p0 = PhaseEquilibrium::pure(eos, t0, None, SolverOptions::default())?
-            .vapor()
-            .pressure(Contributions::Total);
-
-        let b = pc.to_reduced(p0)?.ln() / (1.0 / tc - 1.0 / t0);
-        let a = pc.to_reduced(U::reference_pressure())?.ln() - b.to_reduced(tc)?;
+        Ok(self.predict_with_status(eos)?.values)
+    }
 
+    fn predict_with_status(&self, eos: &Rc<E>) -> Result<Prediction<U>, EstimatorError>
+    where
+        QuantityScalar<U>: std::fmt::Display + std::fmt::LowerExp,
+    {
+        let mut ln_pressure = None;
         let unit = self.target.get(0);
         let mut prediction = Array1::zeros(self.datapoints) * unit;
+        let mut status = Vec::with_capacity(self.datapoints);
         for i in 0..self.datapoints {
             let t = self.temperature.get(i);
-            if let Some(pvap) = PhaseEquilibrium::vapor_pressure(eos, t)[0] {
-                prediction.try_set(i, pvap)?;
-            } else if self.extrapolate {
-                prediction.try_set(i, (a + b.to_reduced(t)?).exp() * U::reference_pressure())?;
-            } else {
-                prediction.try_set(i, f64::NAN * U::reference_pressure())?
+            match PhaseEquilibrium::pure(eos, t, None, self.options.phase_equilibrium) {
+                Ok(vle) => {
+                    prediction.try_set(i, vle.vapor().pressure(Contributions::Total))?;
+                    status.push(PredictionStatus::Converged);
+                }
+                Err(_) if self.extrapolation != Extrapolation::None => {
+                    if ln_pressure.is_none() {
+                        ln_pressure = self.extrapolation.ln_pressure::<U, E>(
+                            eos,
+                            self.max_temperature,
+                            self.options,
+                        )?;
+                    }
+                    let ln_p = ln_pressure.as_ref().unwrap();
+                    let t = t.to_reduced(U::reference_temperature())?;
+                    prediction.try_set(i, ln_p(t).exp() * U::reference_pressure())?;
+                    status.push(PredictionStatus::Extrapolated);
+                }
+                Err(e) => {
+                    prediction.try_set(i, f64::NAN * U::reference_pressure())?;
+                    status.push(PredictionStatus::Failed(e.to_string()));
+                }
             }
         }
-        Ok(prediction)
+        Ok(Prediction {
+            values: prediction,
+            status,
+        })
     }
 
     fn cost(&self, eos: &Rc<E>, loss: Loss) -> Result<Array1<f64>, EstimatorError>
@@ -109,9 +247,152 @@ impl<U: EosUnit, E: EquationOfState> DataSet<U, E> for VaporPressure<U> {
         Ok(cost / self.datapoints as f64)
     }
 
+    fn subset(&self, indices: &[usize]) -> Result<Rc<dyn DataSet<U, E>>, EstimatorError>
+    where
+        U: 'static,
+        E: 'static,
+    {
+        Ok(Rc::new(Self::new(
+            select(&self.target, indices)?,
+            select(&self.temperature, indices)?,
+            self.extrapolation,
+            self.options,
+        )?))
+    }
+
     fn get_input(&self) -> HashMap<String, QuantityArray1<U>> {
         let mut m = HashMap::with_capacity(1);
         m.insert("temperature".to_owned(), self.temperature());
         m
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_eos::propane;
+    use feos_core::cubic::PengRobinson;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    fn vapor_pressure(extrapolation: Extrapolation) -> VaporPressure<SIUnit> {
+        VaporPressure::new(
+            arr1(&[1e6, 5e6, 5e6]) * PASCAL,
+            arr1(&[300.0, 380.0, 400.0]) * KELVIN,
+            extrapolation,
+            PredictionOptions::default(),
+        )
+        .unwrap()
+    }
+
+    /// Reduced critical temperature and ln(p) of the model and
+    /// the extrapolation of the vapor pressure.
+    fn extrapolation(extrapolation: Extrapolation) -> (f64, f64, LnPressure) {
+        let eos = propane();
+        let options = PredictionOptions::default();
+        let cp = State::critical_point(&eos, None, None, options.critical_point).unwrap();
+        let tc = cp
+            .temperature
+            .to_reduced(SIUnit::reference_temperature())
+            .unwrap();
+        let pc = cp.pressure(Contributions::Total);
+        let ln_pc = pc.to_reduced(SIUnit::reference_pressure()).unwrap().ln();
+        let ln_p = extrapolation
+            .ln_pressure::<SIUnit, _>(&eos, 400.0 * KELVIN, options)
+            .unwrap()
+            .unwrap();
+        (tc, ln_pc, ln_p)
+    }
+
+    /// Reduced ln(p) of the model.
+    fn model(t: f64) -> f64 {
+        let vle = PhaseEquilibrium::pure(&propane(), t * KELVIN, None, Default::default()).unwrap();
+        let p = vle.vapor().pressure(Contributions::Total);
+        p.to_reduced(SIUnit::reference_pressure()).unwrap().ln()
+    }
+
+    #[test]
+    fn clausius_clapeyron_line() {
+        let points: Vec<_> = [200.0, 250.0, 300.0]
+            .iter()
+            .map(|&t| (t, 20.0 - 2000.0 / t))
+            .collect();
+        let (a, b) = clausius_clapeyron(&points);
+        assert!((a - 20.0).abs() < 1e-10);
+        assert!((b + 2000.0).abs() < 1e-7);
+    }
+
+    #[test]
+    fn continuous_at_critical_point() {
+        for &e in [
+            Extrapolation::ClausiusClapeyron,
+            Extrapolation::ClausiusClapeyronFit(4),
+            Extrapolation::Antoine,
+            Extrapolation::Penalty(10.0),
+        ]
+        .iter()
+        {
+            let (tc, ln_pc, ln_p) = extrapolation(e);
+            let tolerance = match e {
+                Extrapolation::ClausiusClapeyronFit(_) => 1e-2,
+                _ => 1e-10,
+            };
+            assert!((ln_p(tc) - ln_pc).abs() < tolerance, "{:?}", e);
+        }
+    }
+
+    #[test]
+    fn model_points() {
+        let (tc, _, ln_p) = extrapolation(Extrapolation::ClausiusClapeyron);
+        assert!((ln_p(0.9 * tc) - model(0.9 * tc)).abs() < 1e-10);
+
+        let (tc, _, ln_p) = extrapolation(Extrapolation::Antoine);
+        for &t in [0.7 * tc, 0.85 * tc].iter() {
+            assert!((ln_p(t) - model(t)).abs() < 1e-10);
+        }
+
+        let (tc, ln_pc, ln_p) = extrapolation(Extrapolation::Penalty(10.0));
+        assert!((ln_p(1.1 * tc) - (ln_pc - 1.0)).abs() < 1e-10);
+    }
+
+    #[test]
+    fn predict() {
+        let eos = propane();
+        let prediction = vapor_pressure(Extrapolation::None)
+            .predict_with_status(&eos)
+            .unwrap();
+        assert!(prediction.status[1].is_failed());
+        assert!(prediction.values.get(2).is_nan());
+
+        let prediction = vapor_pressure(Extrapolation::Antoine)
+            .predict_with_status(&eos)
+            .unwrap();
+        assert_eq!(
+            prediction.status,
+            vec![
+                PredictionStatus::Converged,
+                PredictionStatus::Extrapolated,
+                PredictionStatus::Extrapolated
+            ]
+        );
+        assert!(prediction.values.get(2) > prediction.values.get(1));
+
+        // the penalty decreases the vapor pressure above the critical temperature
+        let p = DataSet::<SIUnit, PengRobinson>::predict(
+            &vapor_pressure(Extrapolation::Penalty(10.0)),
+            &eos,
+        )
+        .unwrap();
+        assert!(p.get(2) < p.get(1));
+    }
+
+    #[test]
+    fn concatenate() {
+        let data = vapor_pressure(Extrapolation::Antoine);
+        assert_eq!(data.concatenate(&data).unwrap().temperature().len(), 6);
+        assert!(matches!(
+            data.concatenate(&vapor_pressure(Extrapolation::Penalty(1.0))),
+            Err(EstimatorError::IncompatibleDataSets)
+        ));
+    }
+}